@@ -1,49 +1,198 @@
 /* #region header */
-use chrono::{self, DateTime, Datelike, NaiveDateTime};
+use chrono::{self, DateTime, Datelike, NaiveDate, NaiveDateTime};
 use chrono_tz::Tz;
-use color_eyre::{eyre::Ok, Result};
-use crossterm::event::{self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
-use derive_setters::Setters;
-use hello_user::{ENVIRONMENT_PATH_JSON, LOG_FILE_PATH};
+use color_eyre::{
+    eyre::{eyre, Ok},
+    Result,
+};
+use crossterm::{
+    event::{
+        self, DisableFocusChange, DisableMouseCapture, EnableFocusChange, EnableMouseCapture,
+        Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers, KeyboardEnhancementFlags,
+        MouseEventKind, PopKeyboardEnhancementFlags, PushKeyboardEnhancementFlags,
+    },
+    terminal::{supports_keyboard_enhancement, SetTitle},
+    ExecutableCommand,
+};
+use hello_user::clipboard::CopyToClipboard;
+use hello_user::config::{validate_environment_dict, Config, RunningHistory, WeeklyDistanceReset};
+use hello_user::error::AppError;
+use hello_user::keybindings::{
+    format_key_combination, load_keybinding_map, normalize_key, AppEvent,
+};
+use hello_user::log_message;
+use hello_user::logging::{append_to_log, LogLevel};
+use hello_user::panel_functionalities::astronomy::{moon_phase_icon, next_full_moon};
+use hello_user::panel_functionalities::calendar_todo::{
+    distinct_todo_tags, reorder_todo_items, todo_indices_by_priority, TodoItem,
+};
+use hello_user::panel_functionalities::data_formats::{
+    load_schedule_from_org, load_schedule_from_toml, save_schedule_to_org, save_schedule_to_toml,
+    DataFormat,
+};
+use hello_user::panel_functionalities::health::{calories_for_period, estimate_entry_calories};
+use hello_user::panel_functionalities::heatmap::{
+    heatmap_cell_area, heatmap_date_at, render_calendar_heatmap, HEATMAP_CELL_WIDTH,
+    HEATMAP_DAYS_PER_WEEK, HEATMAP_WEEKS,
+};
+use hello_user::panel_functionalities::running_schedule::{
+    copy_entry_to_date, count_rest_days_in_week, detect_and_fill_missing_dates,
+    detect_duplicate_running_entries, distance_totals_by_month_and_year,
+    filter_schedule_by_session_type, has_logged_distance, prune_old_schedule_entries,
+    running_schedule_to_csv, running_schedule_to_ics, sort_running_schedule,
+    total_distance_for_date_range, ActivityType, RunEntry, RUN_ENTRY_DATE_FORMAT,
+};
+use hello_user::panel_functionalities::season::get_current_season;
+use hello_user::panel_functionalities::statistics::{
+    aggregate_weather_for_run_days, average_temperature_and_precipitation,
+    build_weekly_report_text, compute_training_monotony, compute_week_over_week_change,
+    count_workouts_by_type, current_streak, days_since_last_run, detect_longest_streak,
+    project_yearly_total, recommend_rest_or_run, render_running_schedule_as_sparkline,
+    rolling_average_km, RunningStatistics, ACUTE_TRAINING_LOAD_DAYS, CHRONIC_TRAINING_LOAD_DAYS,
+};
+use hello_user::panel_functionalities::sun_state::{
+    sun_arc_line, DEFAULT_SUNRISE, DEFAULT_SUNSET, SUN_ARC_WIDTH,
+};
+use hello_user::panel_functionalities::weather::{
+    format_weather_value, geocode_city, get_hourly_forecast, get_weather_for_locations,
+    load_api_key, save_api_key, CityWeather, HourlyForecast, WeatherLocation, JENA_COORDINATES,
+};
+use hello_user::panel_functionalities::weather_forecast_panel::WeatherForecastPanel;
+use hello_user::util::{
+    big_text_lines, build_date_to_index_map, center_the_popup_area, current_week_dates,
+    format_date_relative, format_duration, format_km_compact, format_running_summary,
+    format_stopwatch, init_weekday_strings, parse_distance_with_unit,
+    render_block_with_title_and_subtitle, render_border_with_gradient, render_centered_popup,
+    render_centered_stateful_popup, render_empty_state_placeholder, render_form,
+    render_gauge_with_milestone_markers, seconds_until_time_of_day, split_layout_with_min_size,
+    validate_date_string, week_number_for_date, weekday_strings, DistanceUnit, FormField,
+    PopTerminalTitle, PushTerminalTitle, FORM_FIELD_HEIGHT, HEADER_STYLE,
+};
+use hello_user::{ENVIRONMENT_PATH_JSON, LOG_FILE_PATH, SCHEDULE_PATH_ORG, SCHEDULE_PATH_TOML};
 use ratatui::{
-    buffer::Buffer,
+    backend::TestBackend,
     layout::{Constraint, Direction, Flex, Layout, Rect},
     style::{
         palette::tailwind::{self},
-        Color, Modifier, Style, Stylize,
+        Color, Modifier, Style,
     },
     symbols,
     text::{Line, Span, Text},
     widgets::{
-        Block, Borders, Cell, Clear, Gauge, HighlightSpacing, Paragraph, Row, Table, Widget, Wrap,
+        Block, BorderType, Borders, Cell, Clear, Gauge, HighlightSpacing, List, ListState,
+        Paragraph, Row, Table, TableState, Wrap,
     },
-    DefaultTerminal, Frame,
+    DefaultTerminal, Frame, Terminal,
 };
 use std::{
-    collections::HashMap,
+    collections::{HashMap, VecDeque},
     fs::{self, OpenOptions},
+    path::{Path, PathBuf},
 };
 use std::{
-    io::{BufReader, Read, Write},
-    time::Duration,
+    io::{self, stdout},
+    time::{Duration, Instant},
 };
-use tui_textarea::{Key, TextArea};
+use tui_textarea::{CursorMove, TextArea};
 
 const GAUGE4_COLOR: Color = tailwind::ORANGE.c800;
 const DEFAULT_TEXT_COLOR: Color = Color::Yellow;
+const GRADIENT_START_COLOR: Color = tailwind::BLUE.c400;
+const GRADIENT_END_COLOR: Color = tailwind::PINK.c400;
 const REFRESH_RATE_MILLIS: u64 = 500;
+const PAUSED_REFRESH_RATE_MILLIS: u64 = 5000;
+const DEFAULT_SCHEDULE_RETENTION_DAYS: u32 = 365;
 const VERTICAL_SPLIT_PERCENTAGE: u16 = 78;
-const HEADER_STYLE: Style = Style::new().fg(Color::Yellow).add_modifier(Modifier::BOLD);
-const WEEKDAY_STRINGS: [&str; 7] = [
-    "月曜日",
-    "火曜日",
-    "水曜日",
-    "木曜日",
-    "金曜日",
-    "土曜日",
-    "日曜日",
-];
 const VERTICAL_BAR_CHARACTER: &str = " █ ";
+const HIGHLIGHT_SYMBOL_MAX_CHARACTERS: usize = 5;
+const DEFAULT_STALENESS_WARN_DAYS: u32 = 3;
+const TRAINING_MONOTONY_WINDOW_DAYS: u32 = 7;
+/// Monotony above this is shown in red, a common injury risk threshold
+const TRAINING_MONOTONY_WARN_THRESHOLD: f64 = 2.0;
+const STALE_GAUGE_BORDER_SET: symbols::border::Set = symbols::border::Set {
+    top_left: "┌",
+    top_right: "┐",
+    bottom_left: "└",
+    bottom_right: "┘",
+    vertical_left: "╎",
+    vertical_right: "╎",
+    horizontal_top: "╌",
+    horizontal_bottom: "╌",
+};
+const CONTROL_SHIFT: KeyModifiers = KeyModifiers::CONTROL.union(KeyModifiers::SHIFT);
+const HOURLY_FORECAST_HOURS: usize = 24;
+const APP_TITLE: &str = "startup_tui";
+const DEFAULT_PROFILE_NAME: &str = "default";
+const COUNTDOWN_TIME_FORMAT: &str = "%H:%M";
+const WEATHER_ERROR_TIME_FORMAT: &str = "%Y-%m-%d %H:%M";
+/// Weekday and relative date
+const SCHEDULE_HEADER_HEIGHT: u16 = 2;
+const STATUS_FLASH_MILLIS: u64 = 3000;
+const STOPWATCH_REFRESH_RATE_MILLIS: u64 = 100;
+/// Populated config drawn by `--benchmark-render`
+const BENCHMARK_FIXTURE_JSON: &str = include_str!("fixtures/benchmark_resources.json");
+const BENCHMARK_TERMINAL_WIDTH: u16 = 220;
+const BENCHMARK_TERMINAL_HEIGHT: u16 = 50;
+/// Top and bottom border + bar
+const GAUGE_MIN_HEIGHT: u16 = 3;
+/// Weekly totals in the sparkline of the year gauge
+const SPARKLINE_WEEKS: u32 = 12;
+const TOP_WEEKS_COUNT: usize = 5;
+const TODO_TABLE_WIDTHS: [Constraint; 4] = [
+    Constraint::Length(2),
+    Constraint::Length(1),
+    Constraint::Fill(1),
+    Constraint::Length(10),
+];
+const TAG_CHIP_SPACING: u16 = 1;
+/// Built in shortcuts listed in the shortcut panel, in display order
+const KEYBINDING_TABLE: [(&str, AppEvent); 18] = [
+    ("ctrl+r", AppEvent::InsertRun),
+    ("ctrl+t", AppEvent::InsertTodo),
+    ("ctrl+w", AppEvent::AddDistance),
+    ("ctrl+o", AppEvent::ResetWeeklyDistance),
+    ("ctrl+y", AppEvent::YearHeatmap),
+    ("ctrl+shift+a", AppEvent::Statistics),
+    ("ctrl+shift+g", AppEvent::GoalsProgress),
+    ("ctrl+shift+k", AppEvent::CopyWeeklyReport),
+    ("ctrl+shift+t", AppEvent::Countdown),
+    ("ctrl+shift+w", AppEvent::Stopwatch),
+    ("ctrl+shift+n", AppEvent::NewProfile),
+    ("ctrl+shift+f", AppEvent::ToggleWeatherPanel),
+    ("ctrl+shift+e", AppEvent::ConfigureWeatherLocations),
+    ("ctrl+shift+u", AppEvent::TodoListPopup),
+    ("ctrl+shift+b", AppEvent::TagFilter),
+    ("ctrl+shift+d", AppEvent::DeleteScheduleEntry),
+    ("alt+c", AppEvent::CopyScheduleEntry),
+    ("ctrl+shift+h", AppEvent::HourlyForecast),
+];
+/// Days without a run from which the last run reminder turns red
+const LAST_RUN_WARN_DAYS: u64 = 4;
+/// Borders + run day and rest day rows
+const RUN_DAY_WEATHER_HEIGHT: u16 = 4;
+/// Characters of a full bar in the workout type chart of the stats popup
+const WORKOUT_TYPE_BAR_WIDTH: usize = 40;
+/// Only listed in the shortcut panel of debug builds, the overlay doesn't exist in release
+#[cfg(debug_assertions)]
+const DEBUG_OVERLAY_KEY: &str = "f12";
+#[cfg(debug_assertions)]
+const DEBUG_OVERLAY_WIDTH: u16 = 36;
+/// Borders + one row per value
+#[cfg(debug_assertions)]
+const DEBUG_OVERLAY_HEIGHT: u16 = 7;
+const DEFAULT_WEEKLY_GOAL_KM: f64 = 110.0;
+const DEFAULT_MONTHLY_GOAL_KM: f64 = 400.0;
+const DEFAULT_YEARLY_GOAL_KM: f64 = 5000.0;
+/// Completion candidates of the `:` command bar
+const COMMANDS: [&str; 6] = ["q", "w", "reload", "summary", "export csv ", "export ics "];
+/// Panels toggled by ctrl+1 through ctrl+5, in that order
+const TOGGLEABLE_PANELS: [PanelId; 5] = [
+    PanelId::Calendar,
+    PanelId::RunningSchedule,
+    PanelId::Shortcuts,
+    PanelId::Datetime,
+    PanelId::TodoList,
+];
 
 #[derive(Debug, Default)]
 pub struct App<'a> {
@@ -52,8 +201,88 @@ pub struct App<'a> {
     application_state: ApplicationState,
     textarea_widget: TextArea<'a>,
     running_totals: [f64; 3],
-    environment_dict: serde_json::Value,
+    environment_dict: Config,
     shortcut_list_text_block: Paragraph<'a>,
+    hourly_forecast: Option<HourlyForecast>,
+    /// Weather of `environment_dict.weather_locations` that could be fetched, in their order
+    city_weather: Vec<(String, CityWeather)>,
+    /// Latest failure of a weather request, shown in place of the weather rows
+    weather_error: Option<String>,
+    last_weather_fetch_success: Option<NaiveDateTime>,
+    hourly_forecast_table_state: TableState,
+    api_key: Option<String>,
+    /// Panels missing from the map are visible
+    panel_visibility: HashMap<PanelId, bool>,
+    /// Set while the terminal window is unfocused, skips redraws until focus comes back
+    paused: bool,
+    /// Whether the terminal reports shift together with ctrl, see [`App::run`]
+    keyboard_enhancement_enabled: bool,
+    command_textarea: TextArea<'a>,
+    status_message: String,
+    /// Turns every write to disk into a no-op, toggled with ctrl+shift+s or `--read-only`
+    read_only: bool,
+    /// Day under the cursor of the year heatmap, its year is the one shown
+    year_heatmap_selected_date: NaiveDate,
+    /// Grid of the year heatmap as last drawn, for mapping mouse positions to days
+    year_heatmap_area: Rect,
+    /// Figures of the stats popup, refreshed each time it opens
+    running_statistics: RunningStatistics,
+    /// Source of the running schedule, the rest of the config is always json
+    data_format: DataFormat,
+    /// Schedule of the json file while `data_format` reads it from elsewhere, saved back as is
+    json_running_schedule: Vec<RunEntry>,
+    #[cfg(debug_assertions)]
+    debug_overlay_visible: bool,
+    /// Kind of the last event read by the main loop, for the debug overlay
+    #[cfg(debug_assertions)]
+    last_event_type: &'static str,
+    /// How long the previous call of `ui` took, for the debug overlay
+    #[cfg(debug_assertions)]
+    last_frame_duration: Duration,
+    countdown_target: Option<NaiveDateTime>,
+    /// Highlights the status message until this instant
+    status_flash_until: Option<Instant>,
+    /// Set while the stopwatch is running
+    stopwatch_started_at: Option<Instant>,
+    /// Elapsed time of the runs before the last pause
+    stopwatch_accumulated: Duration,
+    /// Shows the weather forecast panel in place of the todo list, which moves to a popup
+    weather_forecast_panel_active: bool,
+    last_save_time: Option<Instant>,
+    /// Set when a write to `environment_path` was refused or failed
+    has_unsaved_changes: bool,
+    selected_todo_index: Option<usize>,
+    /// Row of the selected todo item among the displayed ones
+    todo_table_state: TableState,
+    /// Shows the todo list highest priority first instead of in list order
+    todo_sort_by_priority: bool,
+    /// Cycled with tab, panel specific shortcuts like ctrl+f act on it
+    focused_panel: Option<PanelId>,
+    filtered_schedule_table_state: TableState,
+    /// Selected day column of the running schedule, 1 is today
+    schedule_table_state: TableState,
+    /// Edited copy of `environment_dict.weather_locations`, written back on save
+    weather_locations_draft: Vec<WeatherLocation>,
+    weather_locations_list_state: ListState,
+    /// Set while the `name, lat, lon` input is open, `Some(index)` when editing that location
+    weather_location_input: Option<Option<usize>>,
+    /// Only todo items carrying one of these tags are shown, all items when empty
+    todo_tags_filter: Vec<String>,
+    tag_filter_list_state: ListState,
+    validation_warnings_list_state: ListState,
+    /// Startup popups waiting for the one on screen to close, shown in this order
+    queued_popups: VecDeque<ApplicationState>,
+    /// File `environment_dict` is loaded from and written to, switched by profiles
+    environment_path: PathBuf,
+    active_profile: String,
+    /// Last title sent to the terminal, to only send changes
+    terminal_title: String,
+    /// Marks the selected schedule row, `environment_dict["ui"]["highlight_symbol"]`
+    highlight_symbol: String,
+    /// `Always` keeps a highlight symbol column on every schedule row, toggled with h
+    schedule_highlight_spacing: HighlightSpacing,
+    /// Bindings from `environment_dict["keybindings"]`, checked before the defaults
+    keybinding_map: HashMap<(KeyModifiers, KeyCode), AppEvent>,
 }
 #[derive(Debug, Default, PartialEq)]
 enum ApplicationState {
@@ -63,78 +292,385 @@ enum ApplicationState {
     InsertCalendarItemPopup,
     InsertTodoItemPopup,
     AddToRunningTotals,
+    HourlyForecast,
+    ApiKeyPrompt,
+    ConfirmClearLog,
+    CommandMode,
+    SetCountdown,
+    Countdown,
+    Stopwatch,
+    TodoListPopup,
+    ScheduleFilterPrompt,
+    FilteredScheduleView(String),
+    ValidationWarnings(Vec<String>),
+    TagFilter,
+    NewProfile,
+    /// Date of the schedule entry to delete, `RUN_ENTRY_DATE_FORMAT`
+    ConfirmDeleteEntry(String),
+    /// Date of the schedule entry to copy, `RUN_ENTRY_DATE_FORMAT`
+    CopyEntryToDate(String),
+    ConfigureWeatherLocations,
+    YearHeatmap,
+    Statistics,
+    GoalsProgress,
+    /// Dates with more than one schedule entry, found on startup
+    DuplicateEntryWarning(Vec<NaiveDate>),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+enum PanelId {
+    Calendar,
+    RunningSchedule,
+    Shortcuts,
+    Datetime,
+    TodoList,
 }
 
 /* #endregion */
 
 impl App<'_> {
     pub fn new() -> Self {
-        Self::default()
+        Self {
+            environment_path: PathBuf::from(ENVIRONMENT_PATH_JSON),
+            active_profile: DEFAULT_PROFILE_NAME.to_string(),
+            highlight_symbol: VERTICAL_BAR_CHARACTER.to_string(),
+            schedule_highlight_spacing: HighlightSpacing::Always,
+            ..Self::default()
+        }
+    }
+
+    pub fn read_only(mut self, read_only: bool) -> Self {
+        self.read_only = read_only;
+        self
+    }
+
+    pub fn data_format(mut self, data_format: DataFormat) -> Self {
+        self.data_format = data_format;
+        self
     }
 
+    /// The ctrl+shift shortcuts need a terminal with the kitty keyboard protocol. Legacy
+    /// terminals send ctrl+shift+l as ctrl+l, so there those shortcuts fall back to their ctrl
+    /// counterpart, ctrl+shift+r opens the running schedule editor instead of reconciling the
+    /// totals. Such shortcuts can be rebound in `environment_dict["keybindings"]`
     pub fn run(mut self, mut terminal: DefaultTerminal) -> Result<()> {
         self.setup()?;
         while self.running {
             self.handle_crossterm_events()?;
             self.handle_applicationstates(&mut terminal)?;
         }
+        if self.keyboard_enhancement_enabled {
+            stdout().execute(PopKeyboardEnhancementFlags)?;
+        }
+        stdout().execute(DisableFocusChange)?;
+        stdout().execute(PopTerminalTitle)?;
         Ok(())
     }
 
+    /// Draws `frame_count` frames of the fixture config on a `TestBackend` and returns how long
+    /// each one took, without a real terminal, config file or network access
+    pub fn benchmark_render(mut self, frame_count: usize) -> Result<Vec<Duration>> {
+        self.environment_dict = serde_json::from_str(BENCHMARK_FIXTURE_JSON)?;
+        self.running_totals = self.environment_dict.running_totals;
+        self.read_only = true;
+        self.recompute_running_totals();
+        self.reload_shortcuts_panel();
+        let mut terminal = Terminal::new(TestBackend::new(
+            BENCHMARK_TERMINAL_WIDTH,
+            BENCHMARK_TERMINAL_HEIGHT,
+        ))?;
+        let mut frame_durations = Vec::with_capacity(frame_count);
+        for _ in 0..frame_count {
+            let frame_start = Instant::now();
+            terminal.draw(|frame| self.ui(frame))?;
+            frame_durations.push(frame_start.elapsed());
+        }
+        Ok(frame_durations)
+    }
+
     fn setup(&mut self) -> Result<()> {
         self.running = true;
+        stdout().execute(EnableFocusChange)?;
+        self.keyboard_enhancement_enabled = supports_keyboard_enhancement().unwrap_or(false);
+        if self.keyboard_enhancement_enabled {
+            stdout().execute(PushKeyboardEnhancementFlags(
+                KeyboardEnhancementFlags::DISAMBIGUATE_ESCAPE_CODES,
+            ))?;
+        }
         self.textarea_widget = TextArea::default();
-        self.environment_dict = Self::get_environment_dict();
+        self.environment_dict = self.get_environment_dict()?;
+        self.load_schedule_for_data_format()?;
+        stdout().execute(PushTerminalTitle)?;
+        self.update_terminal_title();
         self.get_running_totals_from_json();
-        self.setup_shortcut_list_textblock();
+        self.get_highlight_symbol_from_json();
+        self.get_keybinding_map_from_json();
+        init_weekday_strings(self.environment_dict.ui.weekday_labels.as_ref());
+        if self.environment_dict.running_history.is_none() {
+            self.migrate_flat_totals_to_per_entry();
+        }
+        self.prune_old_schedule_entries_in_json();
+        self.fill_missing_schedule_dates();
+        self.recompute_running_totals();
+        self.reload_shortcuts_panel();
+        self.api_key =
+            hello_user::default_config_dir().and_then(|config_dir| load_api_key(&config_dir).ok());
+        if self.api_key.is_none() {
+            log_message!("no api key found, prompting for one");
+            self.queued_popups.push_back(ApplicationState::ApiKeyPrompt);
+        }
+        let validation_warnings = validate_environment_dict(&self.environment_dict);
+        for validation_warning in &validation_warnings {
+            log_message!(LogLevel::Warning, validation_warning);
+        }
+        if !validation_warnings.is_empty() {
+            self.validation_warnings_list_state.select_first();
+            self.queued_popups
+                .push_back(ApplicationState::ValidationWarnings(validation_warnings));
+        }
+        let duplicate_dates =
+            detect_duplicate_running_entries(&self.environment_dict.running_schedule);
+        for duplicate_date in &duplicate_dates {
+            log_message!(
+                LogLevel::Warning,
+                &format!(
+                    "running schedule has more than one entry on {}",
+                    duplicate_date.format(RUN_ENTRY_DATE_FORMAT)
+                )
+            );
+        }
+        if !duplicate_dates.is_empty() {
+            self.queued_popups
+                .push_back(ApplicationState::DuplicateEntryWarning(duplicate_dates));
+        }
+        if self.application_state == ApplicationState::Main {
+            self.close_popup();
+        }
+        self.refresh_weather();
         Ok(())
     }
 
-    fn setup_shortcut_list_textblock(&mut self) {
+    /// Shows the next queued popup, or returns to the main view once none is left
+    fn close_popup(&mut self) {
+        self.application_state = self
+            .queued_popups
+            .pop_front()
+            .unwrap_or(ApplicationState::Main);
+    }
+
+    /// Swaps the schedule of the freshly loaded json for the one of the `--format` file
+    fn load_schedule_for_data_format(&mut self) -> core::result::Result<(), AppError> {
+        let running_schedule = match self.data_format {
+            DataFormat::Json => return core::result::Result::Ok(()),
+            DataFormat::Toml => load_schedule_from_toml(Path::new(SCHEDULE_PATH_TOML))?,
+            DataFormat::Org => load_schedule_from_org(Path::new(SCHEDULE_PATH_ORG))?,
+        };
+        self.json_running_schedule = std::mem::replace(
+            &mut self.environment_dict.running_schedule,
+            running_schedule,
+        );
+        core::result::Result::Ok(())
+    }
+
+    fn refresh_weather(&mut self) {
+        self.weather_error = None;
+        self.hourly_forecast = self.fetch_hourly_forecast();
+        self.city_weather = self.fetch_city_weather();
+    }
+
+    fn record_weather_fetch_result(&mut self, error: Option<String>) {
+        match error {
+            Some(e) => self.weather_error = Some(e),
+            None => self.last_weather_fetch_success = Some(chrono::Local::now().naive_local()),
+        }
+    }
+
+    fn fetch_city_weather(&mut self) -> Vec<(String, CityWeather)> {
+        if self.environment_dict.weather_locations.is_empty() {
+            return vec![];
+        }
+        let Some(runtime) = new_tokio_runtime() else {
+            return vec![];
+        };
+        let mut fetched_city_weather = vec![];
+        for (location_name, city_weather) in runtime.block_on(get_weather_for_locations(
+            &self.environment_dict.weather_locations,
+            self.api_key.as_deref().unwrap_or_default(),
+        )) {
+            match city_weather {
+                core::result::Result::Ok(city_weather) => {
+                    self.record_weather_fetch_result(None);
+                    fetched_city_weather.push((location_name, city_weather));
+                }
+                Err(e) => {
+                    log_message!(
+                        LogLevel::Error,
+                        &format!("weather request for {} failed: {}", location_name, e)
+                    );
+                    self.record_weather_fetch_result(Some(format!("{}: {}", location_name, e)));
+                }
+            }
+        }
+        fetched_city_weather
+    }
+
+    fn fetch_hourly_forecast(&mut self) -> Option<HourlyForecast> {
+        let runtime = new_tokio_runtime()?;
+        let (latitude, longitude) = self.hourly_forecast_coordinates(&runtime);
+        match runtime.block_on(get_hourly_forecast(latitude, longitude)) {
+            core::result::Result::Ok(res) => {
+                self.record_weather_fetch_result(None);
+                Some(res)
+            }
+            Err(e) => {
+                log_message!(
+                    LogLevel::Error,
+                    &format!("hourly forecast request failed: {}", e)
+                );
+                self.record_weather_fetch_result(Some(e.to_string()));
+                None
+            }
+        }
+    }
+
+    /// Coordinates of the first weather location, else of `environment_dict.hourly_forecast_city`
+    /// if set, Jena otherwise
+    fn hourly_forecast_coordinates(&mut self, runtime: &tokio::runtime::Runtime) -> (f32, f32) {
+        if let Some(weather_location) = self.environment_dict.weather_locations.first() {
+            return (weather_location.lat as f32, weather_location.lon as f32);
+        }
+        let Some(city_name) = self.environment_dict.hourly_forecast_city.clone() else {
+            return JENA_COORDINATES;
+        };
+        match self.geocode_city_cached(runtime, &city_name) {
+            core::result::Result::Ok((latitude, longitude)) => (latitude as f32, longitude as f32),
+            Err(e) => {
+                log_message!(
+                    LogLevel::Error,
+                    &format!("geocoding {} failed: {}", city_name, e)
+                );
+                JENA_COORDINATES
+            }
+        }
+    }
+
+    /// Serves coordinates from `environment_dict.geocode_cache` and only hits the api on a miss
+    fn geocode_city_cached(
+        &mut self,
+        runtime: &tokio::runtime::Runtime,
+        city_name: &str,
+    ) -> Result<(f64, f64)> {
+        if let Some(&cached_coordinates) = self.environment_dict.geocode_cache.get(city_name) {
+            return Ok(cached_coordinates);
+        }
+        let Some(api_key) = &self.api_key else {
+            return Err(eyre!("no api key to geocode {}", city_name));
+        };
+        let (latitude, longitude) = runtime
+            .block_on(geocode_city(city_name, api_key))
+            .map_err(|e| eyre!("{}", e))?;
+        self.environment_dict
+            .geocode_cache
+            .insert(city_name.to_string(), (latitude, longitude));
+        // in read-only mode the cache only lasts for the session
+        if !self.read_only {
+            self.write_environment_dict_to_json()?;
+        }
+        Ok((latitude, longitude))
+    }
+
+    /// Rebuilds the shortcut panel from `KEYBINDING_TABLE` and the custom keybindings, a
+    /// custom binding replaces the built in shortcut on the same key
+    fn reload_shortcuts_panel(&mut self) {
         let shortcut_key_combination_style = Style::new().fg(Color::LightBlue);
         let important_letter_combination_styled = Style::new()
             .fg(Color::LightBlue)
             .add_modifier(Modifier::BOLD);
-        let shortcut_list_lines = vec![
-            vec![
-                Span::styled("ctrl+r", shortcut_key_combination_style),
-                Span::styled(" edit ", DEFAULT_TEXT_COLOR),
-                Span::styled("r", important_letter_combination_styled),
-                Span::styled("unning schedule", DEFAULT_TEXT_COLOR),
-            ]
-            .into(),
-            vec![
-                Span::styled("ctrl+t", shortcut_key_combination_style),
-                Span::styled(" edit ", DEFAULT_TEXT_COLOR),
-                Span::styled("t", important_letter_combination_styled).add_modifier(Modifier::BOLD),
-                Span::styled("odo list", DEFAULT_TEXT_COLOR),
-            ]
-            .into(),
-            vec![
-                Span::styled("ctrl+w", shortcut_key_combination_style),
-                Span::styled(" add distance to ", DEFAULT_TEXT_COLOR),
-                Span::styled("w", important_letter_combination_styled),
-                Span::styled("eekly total", DEFAULT_TEXT_COLOR),
-            ]
-            .into(),
-            vec![
-                Span::styled("ctrl+o", shortcut_key_combination_style),
-                Span::styled(" reset weekly distance to ", DEFAULT_TEXT_COLOR),
-                Span::styled("0", important_letter_combination_styled),
-            ]
-            .into(),
-        ];
-        self.shortcut_list_text_block = Paragraph::new(shortcut_list_lines);
+        let mut custom_shortcuts: Vec<(String, AppEvent)> = self
+            .keybinding_map
+            .iter()
+            .map(|((modifiers, code), app_event)| {
+                (format_key_combination(*modifiers, *code), *app_event)
+            })
+            .collect();
+        custom_shortcuts
+            .sort_by(|a, b| a.1.description().cmp(b.1.description()).then(a.0.cmp(&b.0)));
+        let mut shortcuts: Vec<(String, AppEvent)> = Vec::new();
+        for (key_combination, app_event) in KEYBINDING_TABLE {
+            shortcuts.extend(
+                custom_shortcuts
+                    .iter()
+                    .filter(|(_, custom_app_event)| *custom_app_event == app_event)
+                    .cloned(),
+            );
+            if custom_shortcuts
+                .iter()
+                .all(|(custom_key_combination, _)| custom_key_combination != key_combination)
+            {
+                shortcuts.push((key_combination.to_string(), app_event));
+            }
+        }
+        #[cfg(debug_assertions)]
+        if custom_shortcuts
+            .iter()
+            .all(|(custom_key_combination, _)| custom_key_combination != DEBUG_OVERLAY_KEY)
+        {
+            shortcuts.push((DEBUG_OVERLAY_KEY.to_string(), AppEvent::ToggleDebugOverlay));
+        }
+        shortcuts.extend(custom_shortcuts.into_iter().filter(|(_, app_event)| {
+            KEYBINDING_TABLE
+                .iter()
+                .all(|(_, table_app_event)| table_app_event != app_event)
+        }));
+        let mut shortcut_spans: Vec<Span> = Vec::new();
+        for (shortcut_index, (key_combination, app_event)) in shortcuts.into_iter().enumerate() {
+            if shortcut_index > 0 {
+                shortcut_spans.push(Span::styled(", ", DEFAULT_TEXT_COLOR));
+            }
+            let description = app_event.description();
+            // the letter of the key is highlighted in the description, the r of running for ctrl+r
+            let important_letter_range = key_combination
+                .rsplit('+')
+                .next()
+                .filter(|key_name| key_name.chars().count() == 1)
+                .and_then(|key_name| {
+                    description
+                        .find(key_name)
+                        .map(|letter_start| (letter_start, letter_start + key_name.len()))
+                });
+            shortcut_spans.push(Span::styled(
+                key_combination.clone(),
+                shortcut_key_combination_style,
+            ));
+            shortcut_spans.push(Span::styled(" ", DEFAULT_TEXT_COLOR));
+            match important_letter_range {
+                Some((letter_start, letter_end)) => {
+                    shortcut_spans.push(Span::styled(
+                        &description[..letter_start],
+                        DEFAULT_TEXT_COLOR,
+                    ));
+                    shortcut_spans.push(Span::styled(
+                        &description[letter_start..letter_end],
+                        important_letter_combination_styled,
+                    ));
+                    shortcut_spans
+                        .push(Span::styled(&description[letter_end..], DEFAULT_TEXT_COLOR));
+                }
+                None => shortcut_spans.push(Span::styled(description, DEFAULT_TEXT_COLOR)),
+            }
+        }
+        self.shortcut_list_text_block =
+            Paragraph::new(Line::from(shortcut_spans)).wrap(Wrap { trim: true });
     }
 
     fn insert_run_popup_drawing(&mut self, terminal: &mut DefaultTerminal) -> Result<()> {
         loop {
-            if let core::result::Result::Ok(Event::Key(key_inner)) = event::read() {
+            if let core::result::Result::Ok(Event::Key(key_inner)) = self.read_popup_event() {
                 if key_inner.code == KeyCode::Esc
                     || key_inner.modifiers == KeyModifiers::CONTROL
                         && key_inner.code == KeyCode::Char('c')
                 {
-                    let _ = append_to_log(&self.textarea_widget.lines().join("\n"));
+                    log_message!(&self.textarea_widget.lines().join("\n"));
                     self.application_state = ApplicationState::Main;
                     return Ok(());
                 }
@@ -148,25 +684,62 @@ impl App<'_> {
         }
     }
 
+    fn insert_todo_item_popup_drawing(&mut self, terminal: &mut DefaultTerminal) -> Result<()> {
+        loop {
+            terminal.draw(|frame| self.ui(frame))?;
+            if let core::result::Result::Ok(Event::Key(key_inner)) = self.read_popup_event() {
+                if key_inner.code == KeyCode::Esc {
+                    self.textarea_widget = TextArea::default();
+                    self.application_state = ApplicationState::Main;
+                    return Ok(());
+                } else if key_inner.code == KeyCode::Enter {
+                    let todo_text = self.textarea_widget.lines().join("").trim().to_string();
+                    if !todo_text.is_empty() && !self.refuse_write_in_read_only_mode() {
+                        self.environment_dict.todo_list.push(TodoItem {
+                            text: todo_text,
+                            ..Default::default()
+                        });
+                        if let Err(e) = self.write_environment_dict_to_json() {
+                            self.status_message = format!("saving todo item failed: {}", e);
+                        }
+                    }
+                    self.textarea_widget = TextArea::default();
+                    self.application_state = ApplicationState::Main;
+                    return Ok(());
+                }
+                self.textarea_widget.input(key_inner);
+            } else {
+                self.application_state = ApplicationState::Main;
+                return Ok(());
+            }
+        }
+    }
+
     fn add_to_running_totals_popup_drawing(
         &mut self,
         terminal: &mut DefaultTerminal,
     ) -> Result<()> {
-        if let core::result::Result::Ok(Event::Key(key_inner)) = event::read() {
+        if let core::result::Result::Ok(Event::Key(key_inner)) = self.read_popup_event() {
             if key_inner.code == KeyCode::Esc
                 || key_inner.modifiers == KeyModifiers::CONTROL
                     && key_inner.code == KeyCode::Char('c')
             {
+                self.application_state = ApplicationState::Main;
                 return Ok(());
             } else if key_inner.code == KeyCode::Enter {
-                let additional_term = &self.textarea_widget.lines().join("\n");
-                let additional_term: f64 = additional_term.parse()?;
-                self.running_totals = [
-                    self.running_totals[0] + additional_term,
-                    self.running_totals[1] + additional_term,
-                    self.running_totals[2] + additional_term,
-                ];
-                self.update_running_totals_in_json()?;
+                let additional_term = self.textarea_widget.lines().join("\n");
+                match parse_distance_with_unit(&additional_term) {
+                    core::result::Result::Ok(_) if self.refuse_write_in_read_only_mode() => {}
+                    core::result::Result::Ok(distance_km) => {
+                        self.add_distance_to_todays_entry(distance_km);
+                        if let Err(e) = self.update_running_totals_in_json() {
+                            self.status_message = format!("saving distance failed: {}", e);
+                        }
+                    }
+                    Err(e) => self.status_message = e.to_string(),
+                }
+                self.textarea_widget = TextArea::default();
+                self.application_state = ApplicationState::Main;
                 return Ok(());
             }
             self.textarea_widget.input(key_inner);
@@ -177,436 +750,3658 @@ impl App<'_> {
         return Ok(());
     }
 
-    fn handle_applicationstates(&mut self, terminal: &mut DefaultTerminal) -> Result<()> {
-        match self.application_state {
-            ApplicationState::InsertRunPopup => {
-                self.insert_run_popup_drawing(terminal)?;
-                return Ok(());
-            }
-            ApplicationState::InsertTodoItemPopup => {
-                self.insert_run_popup_drawing(terminal)?;
-                return Ok(());
-            }
-            ApplicationState::AddToRunningTotals => loop {
-                self.add_to_running_totals_popup_drawing(terminal)?;
-                return Ok(());
-            },
-            _ => {
+    fn hourly_forecast_popup_drawing(&mut self, terminal: &mut DefaultTerminal) -> Result<()> {
+        loop {
+            terminal.draw(|frame| self.ui(frame))?;
+            if let core::result::Result::Ok(Event::Key(key_inner)) = self.read_popup_event() {
+                match key_inner.code {
+                    KeyCode::Esc | KeyCode::Char('q') => {
+                        self.application_state = ApplicationState::Main;
+                        return Ok(());
+                    }
+                    KeyCode::Down | KeyCode::Char('j') => {
+                        self.hourly_forecast_table_state.select_next()
+                    }
+                    KeyCode::Up | KeyCode::Char('k') => {
+                        self.hourly_forecast_table_state.select_previous()
+                    }
+                    _ => {}
+                }
+            } else {
                 self.application_state = ApplicationState::Main;
-                terminal.draw(|frame| self.ui(frame))?;
                 return Ok(());
             }
         }
     }
 
-    fn get_running_totals_from_json(&mut self) {
-        if let Some(running_items) = self.environment_dict["running_totals"].as_array() {
-            for (index, running_item) in running_items.iter().enumerate() {
-                if let Some(running_item) = running_item.as_f64() {
-                    self.running_totals[index] = running_item;
-                } else {
-                    let _ = append_to_log("running totals f64 conversion failed");
-                }
+    /// Popups without input of their own, any key but esc, enter or q keeps them open
+    fn info_popup_drawing(&mut self, terminal: &mut DefaultTerminal) -> Result<()> {
+        loop {
+            terminal.draw(|frame| self.ui(frame))?;
+            match self.read_popup_event() {
+                core::result::Result::Ok(Event::Key(key_inner))
+                    if !matches!(
+                        key_inner.code,
+                        KeyCode::Esc | KeyCode::Enter | KeyCode::Char('q')
+                    ) => {}
+                _ => break,
             }
-        } else {
-            let _ = append_to_log("messed up running totals existing");
         }
+        self.close_popup();
+        Ok(())
     }
 
-    fn handle_crossterm_events(&mut self) -> Result<()> {
-        if !event::poll(Duration::from_millis(REFRESH_RATE_MILLIS))? {
-            return Ok(());
+    fn year_heatmap_popup_drawing(&mut self, terminal: &mut DefaultTerminal) -> Result<()> {
+        loop {
+            terminal.draw(|frame| self.ui(frame))?;
+            let day_offset = match self.read_popup_event() {
+                core::result::Result::Ok(Event::Key(key_inner)) => match key_inner.code {
+                    KeyCode::Esc | KeyCode::Char('q') => break,
+                    KeyCode::Left | KeyCode::Char('h') => -7,
+                    KeyCode::Right | KeyCode::Char('l') => 7,
+                    KeyCode::Up | KeyCode::Char('k') => -1,
+                    KeyCode::Down | KeyCode::Char('j') => 1,
+                    _ => 0,
+                },
+                core::result::Result::Ok(Event::Mouse(mouse_event))
+                    if matches!(
+                        mouse_event.kind,
+                        MouseEventKind::Moved | MouseEventKind::Down(_)
+                    ) =>
+                {
+                    if let Some(date) = heatmap_date_at(
+                        self.year_heatmap_selected_date.year(),
+                        self.year_heatmap_area,
+                        mouse_event.column,
+                        mouse_event.row,
+                    ) {
+                        self.year_heatmap_selected_date = date;
+                    }
+                    0
+                }
+                core::result::Result::Ok(_) => 0,
+                Err(_) => break,
+            };
+            self.year_heatmap_selected_date += chrono::Duration::days(day_offset);
         }
-        match event::read()? {
-            // it's important to check KeyEventKind::Press to avoid handling key release events
-            Event::Key(key) if key.kind == KeyEventKind::Press => self.on_key_event(key),
-            Event::Mouse(_) => {}
-            Event::Resize(_, _) => {}
-            _ => {}
+        stdout().execute(DisableMouseCapture)?;
+        self.application_state = ApplicationState::Main;
+        Ok(())
+    }
+
+    fn api_key_prompt_popup_drawing(&mut self, terminal: &mut DefaultTerminal) -> Result<()> {
+        loop {
+            terminal.draw(|frame| self.ui(frame))?;
+            let core::result::Result::Ok(Event::Key(key_inner)) = self.read_popup_event() else {
+                break;
+            };
+            if key_inner.code == KeyCode::Esc {
+                break;
+            } else if key_inner.code == KeyCode::Enter {
+                let api_key = self.textarea_widget.lines().join("").trim().to_string();
+                if api_key.is_empty() {
+                    continue;
+                }
+                // never log the key itself
+                if !self.refuse_write_in_read_only_mode() {
+                    self.save_api_key_to_config_dir(&api_key);
+                }
+                self.api_key = Some(api_key);
+                break;
+            }
+            self.textarea_widget.input(key_inner);
         }
+        // reset on every way out, the next popup would inherit the mask character otherwise
+        self.textarea_widget = TextArea::default();
+        self.close_popup();
         Ok(())
     }
 
-    /// Handles the key events and updates the state of [`App`].
-    fn on_key_event(&mut self, key: KeyEvent) {
-        match (key.modifiers, key.code) {
-            (_, KeyCode::Esc | KeyCode::Char('q'))
-            | (KeyModifiers::CONTROL, KeyCode::Char('c') | KeyCode::Char('C')) => self.quit(),
-            // Add other key handlers here.
-            (KeyModifiers::CONTROL, KeyCode::Char('l') | KeyCode::Char('L')) => {
-                self.modify_todo_list_popup()
+    fn set_countdown_popup_drawing(&mut self, terminal: &mut DefaultTerminal) -> Result<()> {
+        loop {
+            terminal.draw(|frame| self.ui(frame))?;
+            if let core::result::Result::Ok(Event::Key(key_inner)) = self.read_popup_event() {
+                if key_inner.code == KeyCode::Esc {
+                    self.textarea_widget = TextArea::default();
+                    self.application_state = ApplicationState::Main;
+                    return Ok(());
+                } else if key_inner.code == KeyCode::Enter {
+                    let target_time_text = self.textarea_widget.lines().join("");
+                    let target_time = match chrono::NaiveTime::parse_from_str(
+                        target_time_text.trim(),
+                        COUNTDOWN_TIME_FORMAT,
+                    ) {
+                        core::result::Result::Ok(res) => res,
+                        Err(_) => {
+                            self.status_message =
+                                format!("invalid time {}, expected HH:MM", target_time_text);
+                            continue;
+                        }
+                    };
+                    let now = chrono::Local::now().naive_local();
+                    self.countdown_target = Some(
+                        now + chrono::Duration::seconds(
+                            seconds_until_time_of_day(now, target_time) as i64,
+                        ),
+                    );
+                    self.status_message.clear();
+                    self.textarea_widget = TextArea::default();
+                    self.application_state = ApplicationState::Countdown;
+                    return Ok(());
+                }
+                self.textarea_widget.input(key_inner);
+            } else {
+                self.application_state = ApplicationState::Main;
+                return Ok(());
             }
-            (KeyModifiers::CONTROL, KeyCode::Char('r')) => {
-                self.application_state = ApplicationState::InsertRunPopup;
+        }
+    }
+
+    fn countdown_drawing(&mut self, terminal: &mut DefaultTerminal) -> Result<()> {
+        loop {
+            terminal.draw(|frame| self.ui(frame))?;
+            let Some(countdown_target) = self.countdown_target else {
+                self.application_state = ApplicationState::Main;
+                return Ok(());
+            };
+            if chrono::Local::now().naive_local() >= countdown_target {
+                self.countdown_target = None;
+                self.flash_status_message(format!(
+                    "countdown to {} finished",
+                    countdown_target.format(COUNTDOWN_TIME_FORMAT)
+                ));
+                self.application_state = ApplicationState::Main;
+                return Ok(());
             }
-            (KeyModifiers::CONTROL, KeyCode::Char('t')) => {
-                self.quit();
+            if !event::poll(Duration::from_millis(REFRESH_RATE_MILLIS))? {
+                continue;
             }
-            (KeyModifiers::CONTROL, KeyCode::Char('w')) => {
-                self.application_state = ApplicationState::AddToRunningTotals;
+            if let core::result::Result::Ok(Event::Key(key_inner)) = self.read_popup_event() {
+                if let KeyCode::Esc | KeyCode::Char('q') = key_inner.code {
+                    self.countdown_target = None;
+                    self.application_state = ApplicationState::Main;
+                    return Ok(());
+                }
             }
-            (KeyModifiers::CONTROL, KeyCode::Char('o')) => {
-                self.running_totals = [0.0, self.running_totals[1], self.running_totals[2]];
-                let _ = self.update_running_totals_in_json();
-                let _ = append_to_log("reset weekly distance");
+        }
+    }
+
+    fn stopwatch_drawing(&mut self, terminal: &mut DefaultTerminal) -> Result<()> {
+        loop {
+            terminal.draw(|frame| self.ui(frame))?;
+            if !event::poll(Duration::from_millis(STOPWATCH_REFRESH_RATE_MILLIS))? {
+                continue;
+            }
+            if let core::result::Result::Ok(Event::Key(key_inner)) = self.read_popup_event() {
+                if key_inner.kind != KeyEventKind::Press {
+                    continue;
+                }
+                match key_inner.code {
+                    KeyCode::Esc | KeyCode::Char('q') => {
+                        self.application_state = ApplicationState::Main;
+                        return Ok(());
+                    }
+                    KeyCode::Char(' ') => match self.stopwatch_started_at.take() {
+                        Some(started_at) => self.stopwatch_accumulated += started_at.elapsed(),
+                        None => self.stopwatch_started_at = Some(Instant::now()),
+                    },
+                    KeyCode::Char('r') => {
+                        self.stopwatch_started_at = None;
+                        self.stopwatch_accumulated = Duration::ZERO;
+                    }
+                    KeyCode::Char('l') if self.stopwatch_started_at.is_some() => {
+                        log_message!(&format!(
+                            "stopwatch: {}",
+                            format_stopwatch(self.stopwatch_elapsed())
+                        ));
+                    }
+                    _ => {}
+                }
+            } else {
+                self.application_state = ApplicationState::Main;
+                return Ok(());
             }
-            _ => {}
         }
     }
 
-    fn modify_todo_list_popup(&mut self) {
-        self.application_state = ApplicationState::InsertCalendarItemPopup;
+    fn todo_list_popup_drawing(&mut self, terminal: &mut DefaultTerminal) -> Result<()> {
+        terminal.draw(|frame| self.ui(frame))?;
+        loop {
+            match self.read_popup_event() {
+                core::result::Result::Ok(Event::Key(key_inner))
+                    if key_inner.kind == KeyEventKind::Press =>
+                {
+                    break;
+                }
+                core::result::Result::Ok(_) => {}
+                Err(_) => break,
+            }
+        }
+        self.application_state = ApplicationState::Main;
+        Ok(())
     }
 
-    /// Set running to false to quit the application.
-    fn quit(&mut self) {
-        self.running = false;
+    fn schedule_filter_prompt_drawing(&mut self, terminal: &mut DefaultTerminal) -> Result<()> {
+        loop {
+            terminal.draw(|frame| self.ui(frame))?;
+            if let core::result::Result::Ok(Event::Key(key_inner)) = self.read_popup_event() {
+                if key_inner.code == KeyCode::Esc {
+                    self.textarea_widget = TextArea::default();
+                    self.application_state = ApplicationState::Main;
+                    return Ok(());
+                } else if key_inner.code == KeyCode::Enter {
+                    let session_type = self.textarea_widget.lines().join("").trim().to_string();
+                    self.textarea_widget = TextArea::default();
+                    self.application_state = if session_type.is_empty() {
+                        ApplicationState::Main
+                    } else {
+                        self.filtered_schedule_table_state.select(Some(0));
+                        ApplicationState::FilteredScheduleView(session_type)
+                    };
+                    return Ok(());
+                }
+                self.textarea_widget.input(key_inner);
+            } else {
+                self.application_state = ApplicationState::Main;
+                return Ok(());
+            }
+        }
     }
 
-    fn ui(&mut self, f: &mut Frame) {
-        /* #region layout */
-        let layout_main = Layout::default()
-            .direction(Direction::Horizontal)
-            .margin(1)
-            .constraints(vec![
-                Constraint::Percentage(VERTICAL_SPLIT_PERCENTAGE),
-                Constraint::Fill(1),
-            ])
-            .split(f.area());
-        let layout_left_side = Layout::default()
-            .direction(Direction::Vertical)
-            .vertical_margin(0)
-            .spacing(0)
+    fn filtered_schedule_view_drawing(&mut self, terminal: &mut DefaultTerminal) -> Result<()> {
+        loop {
+            terminal.draw(|frame| self.ui(frame))?;
+            if let core::result::Result::Ok(Event::Key(key_inner)) = self.read_popup_event() {
+                match key_inner.code {
+                    KeyCode::Esc | KeyCode::Char('q') => {
+                        self.application_state = ApplicationState::Main;
+                        return Ok(());
+                    }
+                    KeyCode::Down | KeyCode::Char('j') => {
+                        self.filtered_schedule_table_state.select_next()
+                    }
+                    KeyCode::Up | KeyCode::Char('k') => {
+                        self.filtered_schedule_table_state.select_previous()
+                    }
+                    _ => {}
+                }
+            } else {
+                self.application_state = ApplicationState::Main;
+                return Ok(());
+            }
+        }
+    }
+
+    fn tag_filter_popup_drawing(&mut self, terminal: &mut DefaultTerminal) -> Result<()> {
+        loop {
+            terminal.draw(|frame| self.ui(frame))?;
+            if let core::result::Result::Ok(Event::Key(key_inner)) = self.read_popup_event() {
+                match key_inner.code {
+                    KeyCode::Esc | KeyCode::Char('q') => {
+                        self.application_state = ApplicationState::Main;
+                        return Ok(());
+                    }
+                    KeyCode::Down | KeyCode::Char('j') => self.tag_filter_list_state.select_next(),
+                    KeyCode::Up | KeyCode::Char('k') => {
+                        self.tag_filter_list_state.select_previous()
+                    }
+                    KeyCode::Enter | KeyCode::Char(' ') => {
+                        let todo_tags = distinct_todo_tags(&self.environment_dict.todo_list);
+                        let Some(todo_tag) = self
+                            .tag_filter_list_state
+                            .selected()
+                            .and_then(|index| todo_tags.get(index))
+                        else {
+                            continue;
+                        };
+                        match self
+                            .todo_tags_filter
+                            .iter()
+                            .position(|item| item == todo_tag)
+                        {
+                            Some(index) => {
+                                self.todo_tags_filter.remove(index);
+                            }
+                            None => self.todo_tags_filter.push(todo_tag.clone()),
+                        }
+                    }
+                    _ => {}
+                }
+            } else {
+                self.application_state = ApplicationState::Main;
+                return Ok(());
+            }
+        }
+    }
+
+    fn validation_warnings_popup_drawing(&mut self, terminal: &mut DefaultTerminal) -> Result<()> {
+        loop {
+            terminal.draw(|frame| self.ui(frame))?;
+            if let core::result::Result::Ok(Event::Key(key_inner)) = self.read_popup_event() {
+                match key_inner.code {
+                    KeyCode::Esc | KeyCode::Enter | KeyCode::Char('q') => {
+                        self.close_popup();
+                        return Ok(());
+                    }
+                    KeyCode::Down | KeyCode::Char('j') => {
+                        self.validation_warnings_list_state.select_next()
+                    }
+                    KeyCode::Up | KeyCode::Char('k') => {
+                        self.validation_warnings_list_state.select_previous()
+                    }
+                    _ => {}
+                }
+            } else {
+                self.close_popup();
+                return Ok(());
+            }
+        }
+    }
+
+    fn new_profile_popup_drawing(&mut self, terminal: &mut DefaultTerminal) -> Result<()> {
+        loop {
+            terminal.draw(|frame| self.ui(frame))?;
+            if let core::result::Result::Ok(Event::Key(key_inner)) = self.read_popup_event() {
+                if key_inner.code == KeyCode::Esc {
+                    self.textarea_widget = TextArea::default();
+                    self.application_state = ApplicationState::Main;
+                    return Ok(());
+                } else if key_inner.code == KeyCode::Enter {
+                    let profile_name = self.textarea_widget.lines().join("").trim().to_string();
+                    if let Some(error) = profile_name_error(&profile_name) {
+                        self.status_message = error;
+                        continue;
+                    }
+                    self.create_and_switch_to_profile(&profile_name);
+                    self.textarea_widget = TextArea::default();
+                    self.application_state = ApplicationState::Main;
+                    return Ok(());
+                }
+                self.textarea_widget.input(key_inner);
+            } else {
+                self.application_state = ApplicationState::Main;
+                return Ok(());
+            }
+        }
+    }
+
+    /// Copies the current data to `config_<name>.json` next to `ENVIRONMENT_PATH_JSON` and
+    /// continues on that file
+    fn create_and_switch_to_profile(&mut self, profile_name: &str) {
+        if self.refuse_write_in_read_only_mode() {
+            return;
+        }
+        let profile_path = Path::new(ENVIRONMENT_PATH_JSON)
+            .with_file_name(format!("config_{}.json", profile_name));
+        if profile_path.exists() {
+            self.status_message = format!("profile {} already exists", profile_name);
+            return;
+        }
+        self.sort_running_schedule_in_json();
+        if let Err(e) = self.environment_dict.save(&profile_path) {
+            self.status_message = format!("creating profile {} failed: {}", profile_name, e);
+            return;
+        }
+        self.environment_path = profile_path;
+        self.active_profile = profile_name.to_string();
+        self.has_unsaved_changes = false;
+        self.status_message = format!(
+            "switched to profile {} ({})",
+            profile_name,
+            self.environment_path.display()
+        );
+    }
+
+    /// App name with the active profile on the main screen and the popup name otherwise
+    fn update_terminal_title(&mut self) {
+        let terminal_title = match self.application_state {
+            ApplicationState::Main => format!("{} — {}", APP_TITLE, self.active_profile),
+            ref state => format!("{} — {}", APP_TITLE, describe_application_state(state)),
+        };
+        if terminal_title == self.terminal_title {
+            return;
+        }
+        if let Err(e) = stdout().execute(SetTitle(&terminal_title)) {
+            log_message!(
+                LogLevel::Error,
+                &format!("setting the terminal title failed: {}", e)
+            );
+        }
+        self.terminal_title = terminal_title;
+    }
+
+    fn stopwatch_elapsed(&self) -> Duration {
+        self.stopwatch_accumulated
+            + self
+                .stopwatch_started_at
+                .map_or(Duration::ZERO, |started_at| started_at.elapsed())
+    }
+
+    fn flash_status_message(&mut self, message: String) {
+        self.status_message = message;
+        self.status_flash_until = Some(Instant::now() + Duration::from_millis(STATUS_FLASH_MILLIS));
+    }
+
+    fn save_api_key_to_config_dir(&self, api_key: &str) {
+        match hello_user::default_config_dir() {
+            Some(config_dir) => {
+                if let Err(e) = save_api_key(&config_dir, api_key) {
+                    log_message!(LogLevel::Error, &format!("saving api key failed: {}", e));
+                }
+            }
+            None => {
+                log_message!("no config directory to save the api key in");
+            }
+        }
+    }
+
+    fn confirm_clear_log_popup_drawing(&mut self, terminal: &mut DefaultTerminal) -> Result<()> {
+        terminal.draw(|frame| self.ui(frame))?;
+        if let core::result::Result::Ok(Event::Key(key_inner)) = self.read_popup_event() {
+            if let KeyCode::Char('y') | KeyCode::Char('Y') = key_inner.code {
+                if !self.refuse_write_in_read_only_mode() {
+                    if let Err(e) = clear_log_file() {
+                        self.status_message = format!("couldn't clear log file: {}", e);
+                    }
+                }
+            }
+        }
+        self.application_state = ApplicationState::Main;
+        Ok(())
+    }
+
+    fn confirm_delete_entry_popup_drawing(&mut self, terminal: &mut DefaultTerminal) -> Result<()> {
+        terminal.draw(|frame| self.ui(frame))?;
+        if let core::result::Result::Ok(Event::Key(key_inner)) = self.read_popup_event() {
+            if let (
+                KeyCode::Char('y') | KeyCode::Char('Y'),
+                ApplicationState::ConfirmDeleteEntry(date_string),
+            ) = (key_inner.code, &self.application_state)
+            {
+                let date_string = date_string.clone();
+                if !self.refuse_write_in_read_only_mode() {
+                    self.environment_dict
+                        .running_schedule
+                        .retain(|run_entry| run_entry.date != date_string);
+                    self.status_message = match self.write_environment_dict_to_json() {
+                        core::result::Result::Ok(()) => {
+                            format!("deleted entry for {}", date_string)
+                        }
+                        Err(e) => format!("deleting entry for {} failed: {}", date_string, e),
+                    };
+                }
+            }
+        }
+        self.application_state = ApplicationState::Main;
+        Ok(())
+    }
+
+    fn copy_entry_to_date_popup_drawing(&mut self, terminal: &mut DefaultTerminal) -> Result<()> {
+        loop {
+            terminal.draw(|frame| self.ui(frame))?;
+            if let core::result::Result::Ok(Event::Key(key_inner)) = self.read_popup_event() {
+                if key_inner.code == KeyCode::Esc {
+                    self.textarea_widget = TextArea::default();
+                    self.application_state = ApplicationState::Main;
+                    return Ok(());
+                } else if key_inner.code == KeyCode::Enter {
+                    let new_date_text = self.textarea_widget.lines().join("");
+                    let new_date =
+                        match validate_date_string(new_date_text.trim(), RUN_ENTRY_DATE_FORMAT) {
+                            core::result::Result::Ok(res) => res,
+                            Err(e) => {
+                                self.status_message = format!("{}, expected MM/DD/YYYY", e);
+                                continue;
+                            }
+                        };
+                    self.copy_schedule_entry(new_date);
+                    self.textarea_widget = TextArea::default();
+                    self.application_state = ApplicationState::Main;
+                    return Ok(());
+                }
+                self.textarea_widget.input(key_inner);
+            } else {
+                self.application_state = ApplicationState::Main;
+                return Ok(());
+            }
+        }
+    }
+
+    /// Copies the entry of the `CopyEntryToDate` state to `new_date`, replacing its entry
+    fn copy_schedule_entry(&mut self, new_date: NaiveDate) {
+        if self.refuse_write_in_read_only_mode() {
+            return;
+        }
+        let ApplicationState::CopyEntryToDate(ref source_date) = self.application_state else {
+            return;
+        };
+        let Some(source_entry) = self
+            .environment_dict
+            .running_schedule
+            .iter()
+            .find(|run_entry| run_entry.date == *source_date)
+        else {
+            return;
+        };
+        let copied_entry = copy_entry_to_date(source_entry, new_date);
+        let source_date = source_date.clone();
+        self.environment_dict
+            .running_schedule
+            .retain(|run_entry| run_entry.date != copied_entry.date);
+        let copied_date = copied_entry.date.clone();
+        self.environment_dict.running_schedule.push(copied_entry);
+        self.status_message = match self.write_environment_dict_to_json() {
+            core::result::Result::Ok(()) => {
+                format!("copied entry for {} to {}", source_date, copied_date)
+            }
+            Err(e) => format!("copying entry failed: {}", e),
+        };
+    }
+
+    /// a adds, e edits, d deletes, s saves and esc discards the edits
+    fn configure_weather_locations_popup_drawing(
+        &mut self,
+        terminal: &mut DefaultTerminal,
+    ) -> Result<()> {
+        loop {
+            terminal.draw(|frame| self.ui(frame))?;
+            let core::result::Result::Ok(Event::Key(key_inner)) = self.read_popup_event() else {
+                self.application_state = ApplicationState::Main;
+                return Ok(());
+            };
+            if let Some(edit_index) = self.weather_location_input {
+                match key_inner.code {
+                    KeyCode::Esc => self.weather_location_input = None,
+                    KeyCode::Enter => {
+                        let location_text = self.textarea_widget.lines().join("");
+                        let Some(weather_location) = WeatherLocation::parse(&location_text) else {
+                            self.status_message = format!(
+                                "invalid location {}, expected name, lat, lon",
+                                location_text
+                            );
+                            continue;
+                        };
+                        match edit_index {
+                            Some(index) => self.weather_locations_draft[index] = weather_location,
+                            None => {
+                                self.weather_locations_draft.push(weather_location);
+                                self.weather_locations_list_state
+                                    .select(Some(self.weather_locations_draft.len() - 1));
+                            }
+                        }
+                        self.weather_location_input = None;
+                    }
+                    _ => {
+                        self.textarea_widget.input(key_inner);
+                    }
+                }
+                continue;
+            }
+            let selected_index = self
+                .weather_locations_list_state
+                .selected()
+                .filter(|index| *index < self.weather_locations_draft.len());
+            match key_inner.code {
+                KeyCode::Esc | KeyCode::Char('q') => {
+                    self.application_state = ApplicationState::Main;
+                    return Ok(());
+                }
+                KeyCode::Down | KeyCode::Char('j') => {
+                    self.weather_locations_list_state.select_next()
+                }
+                KeyCode::Up | KeyCode::Char('k') => {
+                    self.weather_locations_list_state.select_previous()
+                }
+                KeyCode::Char('a') => {
+                    self.textarea_widget = TextArea::default();
+                    self.weather_location_input = Some(None);
+                }
+                KeyCode::Char('e') => {
+                    if let Some(index) = selected_index {
+                        self.textarea_widget =
+                            TextArea::from([self.weather_locations_draft[index].to_string()]);
+                        self.textarea_widget.move_cursor(CursorMove::End);
+                        self.weather_location_input = Some(Some(index));
+                    }
+                }
+                KeyCode::Char('d') => {
+                    if let Some(index) = selected_index {
+                        self.weather_locations_draft.remove(index);
+                    }
+                }
+                KeyCode::Char('s') if self.refuse_write_in_read_only_mode() => {
+                    self.application_state = ApplicationState::Main;
+                    return Ok(());
+                }
+                KeyCode::Char('s') => {
+                    self.environment_dict.weather_locations = self.weather_locations_draft.clone();
+                    self.status_message = match self.write_environment_dict_to_json() {
+                        core::result::Result::Ok(()) => "weather locations saved".to_string(),
+                        Err(e) => format!("saving weather locations failed: {}", e),
+                    };
+                    self.refresh_weather();
+                    self.application_state = ApplicationState::Main;
+                    return Ok(());
+                }
+                _ => {}
+            }
+        }
+    }
+
+    fn command_mode_drawing(&mut self, terminal: &mut DefaultTerminal) -> Result<()> {
+        loop {
+            terminal.draw(|frame| self.ui(frame))?;
+            if let core::result::Result::Ok(Event::Key(key_inner)) = self.read_popup_event() {
+                match key_inner.code {
+                    KeyCode::Esc => {
+                        self.command_textarea = TextArea::default();
+                        self.application_state = ApplicationState::Main;
+                        return Ok(());
+                    }
+                    KeyCode::Enter => {
+                        let command = self.command_textarea.lines().join("");
+                        self.command_textarea = TextArea::default();
+                        self.application_state = ApplicationState::Main;
+                        self.execute_command(command.trim());
+                        return Ok(());
+                    }
+                    KeyCode::Tab => self.complete_command(),
+                    _ => {
+                        self.command_textarea.input(key_inner);
+                    }
+                }
+            } else {
+                self.application_state = ApplicationState::Main;
+                return Ok(());
+            }
+        }
+    }
+
+    /// Extends the typed command to the longest prefix shared by all matching [`COMMANDS`]
+    fn complete_command(&mut self) {
+        let typed_command = self.command_textarea.lines().join("");
+        let candidates: Vec<&str> = COMMANDS
+            .into_iter()
+            .filter(|candidate| candidate.starts_with(typed_command.as_str()))
+            .collect();
+        let Some(first_candidate) = candidates.first() else {
+            return;
+        };
+        let common_prefix_length = candidates
+            .iter()
+            .map(|candidate| {
+                first_candidate
+                    .bytes()
+                    .zip(candidate.bytes())
+                    .take_while(|(left, right)| left == right)
+                    .count()
+            })
+            .min()
+            .unwrap_or(0);
+        self.command_textarea =
+            TextArea::new(vec![first_candidate[..common_prefix_length].to_string()]);
+        self.command_textarea.move_cursor(CursorMove::End);
+    }
+
+    fn execute_command(&mut self, command: &str) {
+        let command_parts: Vec<&str> = command.split_whitespace().collect();
+        match command_parts[..] {
+            [] => {}
+            ["q"] => self.quit(),
+            ["w"] => {
+                if self.refuse_write_in_read_only_mode() {
+                    return;
+                }
+                self.status_message = match self.write_environment_dict_to_json() {
+                    core::result::Result::Ok(()) => {
+                        format!("written to {}", self.environment_path.display())
+                    }
+                    Err(e) => format!("write failed: {}", e),
+                }
+            }
+            ["reload"] => {
+                self.status_message = match self.get_environment_dict() {
+                    core::result::Result::Ok(res) => {
+                        self.environment_dict = res;
+                        if let Err(e) = self.load_schedule_for_data_format() {
+                            self.status_message = format!("reload failed: {}", e);
+                            return;
+                        }
+                        self.get_running_totals_from_json();
+                        self.get_highlight_symbol_from_json();
+                        self.get_keybinding_map_from_json();
+                        self.handle_app_event(AppEvent::KeybindingsChanged);
+                        format!("reloaded {}", self.environment_path.display())
+                    }
+                    Err(e) => format!("reload failed: {}", e),
+                }
+            }
+            ["summary"] => {
+                let running_schedule = self.get_running_schedule_from_json();
+                let today = chrono::Local::now().date_naive();
+                let day_total = total_distance_for_date_range(&running_schedule, today, today);
+                let distance_unit = match self.environment_dict.settings.distance_unit.as_deref() {
+                    Some("mi") => DistanceUnit::Miles,
+                    _ => DistanceUnit::Kilometres,
+                };
+                self.status_message = format_running_summary(
+                    &[
+                        day_total,
+                        self.running_totals[0],
+                        self.running_totals[1],
+                        self.running_totals[2],
+                    ],
+                    distance_unit,
+                );
+                if let Some(week_calories) = self.week_calories() {
+                    self.status_message += &format!(" | Week: ~{:.0} kcal", week_calories);
+                }
+                let (longest_streak_days, longest_streak_end) =
+                    detect_longest_streak(&running_schedule);
+                if longest_streak_days > 0 {
+                    self.status_message += &format!(
+                        " | Personal Best Streak: {} days (ended {})",
+                        longest_streak_days,
+                        longest_streak_end.format(RUN_ENTRY_DATE_FORMAT)
+                    );
+                }
+            }
+            ["export", export_format @ ("csv" | "ics"), export_path] => {
+                if self.refuse_write_in_read_only_mode() {
+                    return;
+                }
+                let running_schedule = self.get_running_schedule_from_json();
+                let exported_schedule = if export_format == "csv" {
+                    running_schedule_to_csv(&running_schedule)
+                } else {
+                    running_schedule_to_ics(&running_schedule)
+                };
+                self.status_message = match fs::write(export_path, exported_schedule) {
+                    core::result::Result::Ok(()) => {
+                        format!("exported {} to {}", export_format, export_path)
+                    }
+                    Err(e) => format!("export failed: {}", e),
+                }
+            }
+            _ => self.status_message = format!("unknown command: {}", command),
+        }
+    }
+
+    fn handle_applicationstates(&mut self, terminal: &mut DefaultTerminal) -> Result<()> {
+        self.update_terminal_title();
+        match self.application_state {
+            ApplicationState::InsertRunPopup => {
+                self.insert_run_popup_drawing(terminal)?;
+                return Ok(());
+            }
+            ApplicationState::InsertTodoItemPopup => {
+                self.insert_todo_item_popup_drawing(terminal)?;
+                return Ok(());
+            }
+            ApplicationState::AddToRunningTotals => loop {
+                self.add_to_running_totals_popup_drawing(terminal)?;
+                return Ok(());
+            },
+            ApplicationState::HourlyForecast => {
+                self.hourly_forecast_popup_drawing(terminal)?;
+                return Ok(());
+            }
+            ApplicationState::ApiKeyPrompt => {
+                self.api_key_prompt_popup_drawing(terminal)?;
+                return Ok(());
+            }
+            ApplicationState::ConfirmClearLog => {
+                self.confirm_clear_log_popup_drawing(terminal)?;
+                return Ok(());
+            }
+            ApplicationState::ConfirmDeleteEntry(_) => {
+                self.confirm_delete_entry_popup_drawing(terminal)?;
+                return Ok(());
+            }
+            ApplicationState::CopyEntryToDate(_) => {
+                self.copy_entry_to_date_popup_drawing(terminal)?;
+                return Ok(());
+            }
+            ApplicationState::ConfigureWeatherLocations => {
+                self.configure_weather_locations_popup_drawing(terminal)?;
+                return Ok(());
+            }
+            ApplicationState::YearHeatmap => {
+                self.year_heatmap_popup_drawing(terminal)?;
+                return Ok(());
+            }
+            ApplicationState::Statistics
+            | ApplicationState::GoalsProgress
+            | ApplicationState::DuplicateEntryWarning(_) => {
+                self.info_popup_drawing(terminal)?;
+                return Ok(());
+            }
+            ApplicationState::CommandMode => {
+                self.command_mode_drawing(terminal)?;
+                return Ok(());
+            }
+            ApplicationState::SetCountdown => {
+                self.set_countdown_popup_drawing(terminal)?;
+                return Ok(());
+            }
+            ApplicationState::Countdown => {
+                self.countdown_drawing(terminal)?;
+                return Ok(());
+            }
+            ApplicationState::Stopwatch => {
+                self.stopwatch_drawing(terminal)?;
+                return Ok(());
+            }
+            ApplicationState::TodoListPopup => {
+                self.todo_list_popup_drawing(terminal)?;
+                return Ok(());
+            }
+            ApplicationState::ScheduleFilterPrompt => {
+                self.schedule_filter_prompt_drawing(terminal)?;
+                return Ok(());
+            }
+            ApplicationState::FilteredScheduleView(_) => {
+                self.filtered_schedule_view_drawing(terminal)?;
+                return Ok(());
+            }
+            ApplicationState::TagFilter => {
+                self.tag_filter_popup_drawing(terminal)?;
+                return Ok(());
+            }
+            ApplicationState::NewProfile => {
+                self.new_profile_popup_drawing(terminal)?;
+                return Ok(());
+            }
+            ApplicationState::ValidationWarnings(_) => {
+                self.validation_warnings_popup_drawing(terminal)?;
+                return Ok(());
+            }
+            _ => {
+                self.application_state = ApplicationState::Main;
+                if !self.paused {
+                    terminal.draw(|frame| self.ui(frame))?;
+                }
+                return Ok(());
+            }
+        }
+    }
+
+    fn get_keybinding_map_from_json(&mut self) {
+        let (keybinding_map, warnings) = load_keybinding_map(&self.environment_dict.keybindings);
+        for warning in &warnings {
+            log_message!(LogLevel::Warning, warning);
+        }
+        self.keybinding_map = keybinding_map;
+    }
+
+    /// Falls back to `VERTICAL_BAR_CHARACTER` when unset or not 1 to 5 characters long
+    fn get_highlight_symbol_from_json(&mut self) {
+        self.highlight_symbol = match &self.environment_dict.ui.highlight_symbol {
+            Some(highlight_symbol)
+                if (1..=HIGHLIGHT_SYMBOL_MAX_CHARACTERS)
+                    .contains(&highlight_symbol.chars().count()) =>
+            {
+                highlight_symbol.clone()
+            }
+            Some(highlight_symbol) => {
+                log_message!(
+                    LogLevel::Warning,
+                    &format!(
+                        "highlight symbol {:?} is not 1 to {} characters, using the default",
+                        highlight_symbol, HIGHLIGHT_SYMBOL_MAX_CHARACTERS
+                    )
+                );
+                VERTICAL_BAR_CHARACTER.to_string()
+            }
+            None => VERTICAL_BAR_CHARACTER.to_string(),
+        };
+    }
+
+    /// Time since `running_totals` last changed, `None` if that was never recorded
+    fn elapsed_since_last_update(&self) -> Option<Duration> {
+        self.environment_dict
+            .running_totals_last_updated
+            .and_then(|last_updated| {
+                (chrono::Local::now().naive_local() - last_updated)
+                    .to_std()
+                    .ok()
+            })
+    }
+
+    fn get_running_totals_from_json(&mut self) {
+        self.running_totals = self.environment_dict.running_totals;
+    }
+
+    /// One-time move from the flat `running_totals` to totals recomputed from the
+    /// per-entry distances, stored in `environment_dict.running_history`
+    fn migrate_flat_totals_to_per_entry(&mut self) {
+        let running_schedule = self.get_running_schedule_from_json();
+        if !has_logged_distance(&running_schedule) {
+            log_message!("no per entry distances yet, keeping flat running totals");
+            return;
+        }
+        let (monthly_totals, yearly_totals) = distance_totals_by_month_and_year(&running_schedule);
+        let today = chrono::Local::now();
+        let current_month_total = *monthly_totals
+            .get(&today.format("%Y-%m").to_string())
+            .unwrap_or(&0.0);
+        let current_year_total = *yearly_totals
+            .get(&today.format("%Y").to_string())
+            .unwrap_or(&0.0);
+        self.environment_dict.running_history = Some(RunningHistory {
+            monthly: monthly_totals,
+            yearly: yearly_totals,
+        });
+        self.running_totals[1] = current_month_total;
+        self.running_totals[2] = current_year_total;
+        if self.read_only {
+            log_message!("read-only mode, running totals migrated for this session only");
+            return;
+        }
+        match self.update_running_totals_in_json() {
+            core::result::Result::Ok(()) => {
+                log_message!("migrated running totals to per entry history");
+            }
+            Err(e) => {
+                log_message!(
+                    LogLevel::Error,
+                    &format!("running history migration failed: {}", e)
+                );
+            }
+        }
+    }
+
+    /// Adds `distance_km` to the schedule entry of today, creating it if needed
+    fn add_distance_to_todays_entry(&mut self, distance_km: f64) {
+        let today = chrono::Local::now()
+            .date_naive()
+            .format(RUN_ENTRY_DATE_FORMAT)
+            .to_string();
+        let running_schedule = &mut self.environment_dict.running_schedule;
+        let run_entry_index = match running_schedule
+            .iter()
+            .position(|run_entry| run_entry.date == today)
+        {
+            Some(run_entry_index) => run_entry_index,
+            None => {
+                running_schedule.push(RunEntry {
+                    date: today,
+                    ..Default::default()
+                });
+                running_schedule.len() - 1
+            }
+        };
+        let run_entry = &mut running_schedule[run_entry_index];
+        run_entry.distance_km = Some(run_entry.distance_km.unwrap_or(0.0) + distance_km);
+        self.recompute_running_totals();
+    }
+
+    /// Week, month and year totals from the schedule entries, which are authoritative.
+    /// Schedules without any logged distance keep the flat totals of older files
+    fn recompute_running_totals(&mut self) {
+        let running_schedule = &self.environment_dict.running_schedule;
+        if !has_logged_distance(running_schedule) {
+            return;
+        }
+        let today = chrono::Local::now().date_naive();
+        let week_start = self.current_week_start();
+        let month_start = today.with_day(1).unwrap_or(today);
+        let year_start = today.with_ordinal(1).unwrap_or(today);
+        let week_offset_km = self
+            .environment_dict
+            .weekly_distance_reset
+            .filter(|weekly_distance_reset| weekly_distance_reset.week_start == week_start)
+            .map_or(0.0, |weekly_distance_reset| weekly_distance_reset.offset_km);
+        self.running_totals = [
+            (total_distance_for_date_range(running_schedule, week_start, today) - week_offset_km)
+                .max(0.0),
+            total_distance_for_date_range(running_schedule, month_start, today),
+            total_distance_for_date_range(running_schedule, year_start, today),
+        ];
+    }
+
+    fn current_week_start(&self) -> NaiveDate {
+        let [week_start, ..] = current_week_dates(
+            self.environment_dict
+                .settings
+                .week_start
+                .unwrap_or_default(),
+        );
+        week_start
+    }
+
+    fn reconcile_running_totals(&mut self) {
+        if self.refuse_write_in_read_only_mode() {
+            return;
+        }
+        self.recompute_running_totals();
+        match self.update_running_totals_in_json() {
+            core::result::Result::Ok(()) => {
+                self.status_message = "running totals reconciled with schedule".to_string();
+            }
+            Err(e) => {
+                self.status_message = format!("saving reconciled running totals failed: {}", e);
+            }
+        }
+    }
+
+    fn prune_old_schedule_entries_in_json(&mut self) {
+        if self.read_only {
+            log_message!("read-only mode, old running schedule entries are kept");
+            return;
+        }
+        let keep_days = self
+            .environment_dict
+            .settings
+            .schedule_retention_days
+            .unwrap_or(DEFAULT_SCHEDULE_RETENTION_DAYS);
+        let running_schedule = &mut self.environment_dict.running_schedule;
+        let entry_count_before = running_schedule.len();
+        prune_old_schedule_entries(running_schedule, keep_days);
+        let removed_entry_count = entry_count_before - running_schedule.len();
+        if removed_entry_count == 0 {
+            return;
+        }
+        match self.write_environment_dict_to_json() {
+            core::result::Result::Ok(()) => {
+                log_message!(&format!(
+                    "pruned {} running schedule entries older than {} days",
+                    removed_entry_count, keep_days
+                ));
+            }
+            Err(e) => {
+                log_message!(
+                    LogLevel::Error,
+                    &format!("writing pruned running schedule failed: {}", e)
+                );
+            }
+        }
+    }
+
+    fn fill_missing_schedule_dates(&mut self) {
+        detect_and_fill_missing_dates(&mut self.environment_dict.running_schedule);
+    }
+
+    fn sort_running_schedule_in_json(&mut self) {
+        sort_running_schedule(&mut self.environment_dict.running_schedule);
+    }
+
+    fn get_running_schedule_from_json(&self) -> Vec<RunEntry> {
+        self.environment_dict.running_schedule.clone()
+    }
+
+    /// Next event for a popup loop. Focus changes only update `paused`, so switching windows
+    /// doesn't close the open popup
+    fn read_popup_event(&mut self) -> io::Result<Event> {
+        loop {
+            match event::read()? {
+                Event::FocusLost => self.paused = true,
+                Event::FocusGained => self.paused = false,
+                event => return core::result::Result::Ok(event),
+            }
+        }
+    }
+
+    fn handle_crossterm_events(&mut self) -> Result<()> {
+        let refresh_rate_millis = if self.paused {
+            PAUSED_REFRESH_RATE_MILLIS
+        } else {
+            REFRESH_RATE_MILLIS
+        };
+        if !event::poll(Duration::from_millis(refresh_rate_millis))? {
+            return Ok(());
+        }
+        let event = event::read()?;
+        #[cfg(debug_assertions)]
+        {
+            self.last_event_type = match event {
+                Event::Key(_) => "key",
+                Event::Mouse(_) => "mouse",
+                Event::Resize(_, _) => "resize",
+                Event::FocusGained | Event::FocusLost => "focus",
+                Event::Paste(_) => "paste",
+            };
+        }
+        match event {
+            // it's important to check KeyEventKind::Press to avoid handling key release events
+            Event::Key(key) if key.kind == KeyEventKind::Press => self.on_key_event(key),
+            Event::FocusLost => self.paused = true,
+            Event::FocusGained => self.paused = false,
+            Event::Mouse(_) => {}
+            Event::Resize(_, _) => {}
+            _ => {}
+        }
+        Ok(())
+    }
+
+    /// Handles the key events and updates the state of [`App`].
+    fn on_key_event(&mut self, key: KeyEvent) {
+        if let Some(app_event) = self
+            .keybinding_map
+            .get(&normalize_key(key.modifiers, key.code))
+        {
+            self.handle_app_event(*app_event);
+            return;
+        }
+        match (key.modifiers, key.code) {
+            (_, KeyCode::Esc | KeyCode::Char('q'))
+            | (KeyModifiers::CONTROL, KeyCode::Char('c') | KeyCode::Char('C')) => {
+                self.handle_app_event(AppEvent::Quit)
+            }
+            // Add other key handlers here.
+            (_, KeyCode::Char(':')) => self.handle_app_event(AppEvent::CommandMode),
+            (KeyModifiers::CONTROL, KeyCode::Char('l') | KeyCode::Char('L')) => {
+                self.handle_app_event(AppEvent::InsertCalendarItem)
+            }
+            (KeyModifiers::CONTROL, KeyCode::Char('r')) => {
+                self.handle_app_event(AppEvent::InsertRun)
+            }
+            (KeyModifiers::CONTROL, KeyCode::Char('t')) => {
+                self.handle_app_event(AppEvent::InsertTodo)
+            }
+            (KeyModifiers::CONTROL, KeyCode::Char('w')) => {
+                self.handle_app_event(AppEvent::AddDistance)
+            }
+            (CONTROL_SHIFT, KeyCode::Char('s') | KeyCode::Char('S')) => {
+                self.handle_app_event(AppEvent::ToggleReadOnly)
+            }
+            (CONTROL_SHIFT, KeyCode::Char('r') | KeyCode::Char('R')) => {
+                self.handle_app_event(AppEvent::ReconcileTotals)
+            }
+            (CONTROL_SHIFT, KeyCode::Char('l') | KeyCode::Char('L')) => {
+                self.handle_app_event(AppEvent::ClearLog)
+            }
+            (CONTROL_SHIFT, KeyCode::Char('t') | KeyCode::Char('T')) => {
+                self.handle_app_event(AppEvent::Countdown)
+            }
+            (CONTROL_SHIFT, KeyCode::Char('w') | KeyCode::Char('W')) => {
+                self.handle_app_event(AppEvent::Stopwatch)
+            }
+            (KeyModifiers::CONTROL, KeyCode::Char('y')) => {
+                self.handle_app_event(AppEvent::YearHeatmap)
+            }
+            (CONTROL_SHIFT, KeyCode::Char('a') | KeyCode::Char('A')) => {
+                self.handle_app_event(AppEvent::Statistics)
+            }
+            (CONTROL_SHIFT, KeyCode::Char('g') | KeyCode::Char('G')) => {
+                self.handle_app_event(AppEvent::GoalsProgress)
+            }
+            (CONTROL_SHIFT, KeyCode::Char('k') | KeyCode::Char('K')) => {
+                self.handle_app_event(AppEvent::CopyWeeklyReport)
+            }
+            (CONTROL_SHIFT, KeyCode::Char('f') | KeyCode::Char('F')) => {
+                self.handle_app_event(AppEvent::ToggleWeatherPanel)
+            }
+            (CONTROL_SHIFT, KeyCode::Char('e') | KeyCode::Char('E')) => {
+                self.handle_app_event(AppEvent::ConfigureWeatherLocations)
+            }
+            (CONTROL_SHIFT, KeyCode::Char('d') | KeyCode::Char('D')) => {
+                self.handle_app_event(AppEvent::DeleteScheduleEntry)
+            }
+            (CONTROL_SHIFT, KeyCode::Char('u') | KeyCode::Char('U')) => {
+                self.handle_app_event(AppEvent::TodoListPopup)
+            }
+            (CONTROL_SHIFT, KeyCode::Char('b') | KeyCode::Char('B')) => {
+                self.handle_app_event(AppEvent::TagFilter)
+            }
+            // not ctrl+shift+c, most terminals send that as ctrl+c or keep it for their own copy
+            (KeyModifiers::ALT, KeyCode::Char('c')) => {
+                self.handle_app_event(AppEvent::CopyScheduleEntry)
+            }
+            (CONTROL_SHIFT, KeyCode::Char('n') | KeyCode::Char('N')) => {
+                self.handle_app_event(AppEvent::NewProfile)
+            }
+            (CONTROL_SHIFT, KeyCode::Char('p') | KeyCode::Char('P')) => {
+                self.handle_app_event(AppEvent::TogglePrioritySort)
+            }
+            (CONTROL_SHIFT, KeyCode::Char('h') | KeyCode::Char('H')) => {
+                self.handle_app_event(AppEvent::HourlyForecast)
+            }
+            (KeyModifiers::CONTROL, KeyCode::Char(digit @ '1'..='5')) => {
+                let panel_id = TOGGLEABLE_PANELS[digit as usize - '1' as usize];
+                self.toggle_panel_visibility(panel_id);
+            }
+            (KeyModifiers::NONE, KeyCode::Tab) => self.handle_app_event(AppEvent::FocusNextPanel),
+            (KeyModifiers::NONE, KeyCode::F(12)) => {
+                self.handle_app_event(AppEvent::ToggleDebugOverlay)
+            }
+            (KeyModifiers::NONE, KeyCode::Char('h')) => {
+                self.handle_app_event(AppEvent::ToggleHighlightSpacing)
+            }
+            (KeyModifiers::CONTROL, KeyCode::Char('f')) => {
+                self.handle_app_event(AppEvent::FilterSchedule)
+            }
+            (KeyModifiers::NONE, KeyCode::Left)
+                if self.focused_panel == Some(PanelId::RunningSchedule) =>
+            {
+                self.select_schedule_day(-1)
+            }
+            (KeyModifiers::NONE, KeyCode::Right)
+                if self.focused_panel == Some(PanelId::RunningSchedule) =>
+            {
+                self.select_schedule_day(1)
+            }
+            (KeyModifiers::NONE, KeyCode::Up) => self.select_todo_item(-1),
+            (KeyModifiers::NONE, KeyCode::Down) => self.select_todo_item(1),
+            (KeyModifiers::ALT, KeyCode::Up) => self.move_selected_todo_item(-1),
+            (KeyModifiers::ALT, KeyCode::Down) => self.move_selected_todo_item(1),
+            (KeyModifiers::CONTROL, KeyCode::Char('o')) => {
+                self.handle_app_event(AppEvent::ResetWeeklyDistance)
+            }
+            _ => {}
+        }
+    }
+
+    fn handle_app_event(&mut self, app_event: AppEvent) {
+        match app_event {
+            AppEvent::Quit => self.quit(),
+            AppEvent::CommandMode => {
+                self.status_message.clear();
+                self.application_state = ApplicationState::CommandMode;
+            }
+            AppEvent::InsertCalendarItem => self.modify_todo_list_popup(),
+            AppEvent::InsertRun => {
+                self.application_state = ApplicationState::InsertRunPopup;
+            }
+            AppEvent::InsertTodo => {
+                self.textarea_widget = TextArea::default();
+                self.application_state = ApplicationState::InsertTodoItemPopup;
+            }
+            AppEvent::AddDistance => {
+                self.application_state = ApplicationState::AddToRunningTotals;
+            }
+            AppEvent::ResetWeeklyDistance => {
+                if self.refuse_write_in_read_only_mode() {
+                    return;
+                }
+                let week_start = self.current_week_start();
+                self.environment_dict.weekly_distance_reset = Some(WeeklyDistanceReset {
+                    week_start,
+                    offset_km: total_distance_for_date_range(
+                        &self.environment_dict.running_schedule,
+                        week_start,
+                        chrono::Local::now().date_naive(),
+                    ),
+                });
+                self.running_totals[0] = 0.0;
+                if let Err(e) = self.update_running_totals_in_json() {
+                    self.status_message = format!("saving weekly distance reset failed: {}", e);
+                }
+                log_message!("reset weekly distance");
+            }
+            AppEvent::ToggleReadOnly => {
+                self.read_only = !self.read_only;
+                self.status_message = if self.read_only {
+                    "Read-only mode on".to_string()
+                } else {
+                    "Read-only mode off".to_string()
+                };
+            }
+            AppEvent::ReconcileTotals => self.reconcile_running_totals(),
+            AppEvent::ClearLog => {
+                self.application_state = ApplicationState::ConfirmClearLog;
+            }
+            AppEvent::Countdown => {
+                self.textarea_widget = TextArea::default();
+                self.application_state = ApplicationState::SetCountdown;
+            }
+            AppEvent::TagFilter => {
+                self.tag_filter_list_state.select(Some(0));
+                self.application_state = ApplicationState::TagFilter;
+            }
+            AppEvent::Stopwatch => {
+                self.application_state = ApplicationState::Stopwatch;
+            }
+            AppEvent::ToggleWeatherPanel => {
+                self.weather_forecast_panel_active = !self.weather_forecast_panel_active;
+            }
+            // the todo list only needs a popup while the weather panel covers it
+            AppEvent::TodoListPopup if self.weather_forecast_panel_active => {
+                self.application_state = ApplicationState::TodoListPopup;
+            }
+            AppEvent::NewProfile => {
+                self.textarea_widget = TextArea::default();
+                self.application_state = ApplicationState::NewProfile;
+            }
+            AppEvent::TogglePrioritySort => {
+                self.todo_sort_by_priority = !self.todo_sort_by_priority;
+            }
+            AppEvent::HourlyForecast => {
+                self.hourly_forecast_table_state.select(Some(0));
+                self.application_state = ApplicationState::HourlyForecast;
+            }
+            AppEvent::FocusNextPanel => self.focus_next_panel(),
+            AppEvent::ToggleHighlightSpacing => {
+                self.schedule_highlight_spacing = match self.schedule_highlight_spacing {
+                    HighlightSpacing::Always => HighlightSpacing::WhenSelected,
+                    _ => HighlightSpacing::Always,
+                };
+            }
+            AppEvent::FilterSchedule if self.focused_panel == Some(PanelId::RunningSchedule) => {
+                self.textarea_widget = TextArea::default();
+                self.application_state = ApplicationState::ScheduleFilterPrompt;
+            }
+            AppEvent::DeleteScheduleEntry => {
+                let Some(date_string) = self
+                    .selected_schedule_date()
+                    .map(|date| date.format(RUN_ENTRY_DATE_FORMAT).to_string())
+                else {
+                    self.status_message = "select a day with left/right first".to_string();
+                    return;
+                };
+                if self
+                    .environment_dict
+                    .running_schedule
+                    .iter()
+                    .any(|run_entry| run_entry.date == date_string)
+                {
+                    self.application_state = ApplicationState::ConfirmDeleteEntry(date_string);
+                } else {
+                    self.status_message = format!("no entry for {}", date_string);
+                }
+            }
+            AppEvent::CopyScheduleEntry => {
+                let Some(date_string) = self
+                    .selected_schedule_date()
+                    .map(|date| date.format(RUN_ENTRY_DATE_FORMAT).to_string())
+                else {
+                    self.status_message = "select a day with left/right first".to_string();
+                    return;
+                };
+                if self
+                    .environment_dict
+                    .running_schedule
+                    .iter()
+                    .any(|run_entry| run_entry.date == date_string)
+                {
+                    self.textarea_widget = TextArea::default();
+                    self.application_state = ApplicationState::CopyEntryToDate(date_string);
+                } else {
+                    self.status_message = format!("no entry for {}", date_string);
+                }
+            }
+            AppEvent::KeybindingsChanged => self.reload_shortcuts_panel(),
+            AppEvent::ToggleDebugOverlay => {
+                #[cfg(debug_assertions)]
+                {
+                    self.debug_overlay_visible = !self.debug_overlay_visible;
+                }
+            }
+            AppEvent::ConfigureWeatherLocations => {
+                self.weather_locations_draft = self.environment_dict.weather_locations.clone();
+                self.weather_locations_list_state.select(Some(0));
+                self.weather_location_input = None;
+                self.application_state = ApplicationState::ConfigureWeatherLocations;
+            }
+            AppEvent::Statistics => {
+                self.running_statistics = RunningStatistics::from_schedule(
+                    &self.environment_dict.running_schedule,
+                    TOP_WEEKS_COUNT,
+                    self.monthly_goal_km(),
+                );
+                self.application_state = ApplicationState::Statistics;
+            }
+            AppEvent::GoalsProgress => {
+                self.application_state = ApplicationState::GoalsProgress;
+            }
+            AppEvent::CopyWeeklyReport => {
+                let weekly_report = build_weekly_report_text(
+                    &self.environment_dict.running_schedule,
+                    self.city_weather
+                        .first()
+                        .map(|(_, city_weather)| city_weather),
+                    self.environment_dict
+                        .settings
+                        .week_start
+                        .unwrap_or_default(),
+                );
+                self.status_message = match stdout().execute(CopyToClipboard(&weekly_report)) {
+                    core::result::Result::Ok(_) => "Weekly report copied to clipboard".to_string(),
+                    Err(e) => format!("copying weekly report failed: {}", e),
+                };
+            }
+            AppEvent::YearHeatmap => {
+                // only captured while the heatmap is open, so text selection keeps working
+                if let Err(e) = stdout().execute(EnableMouseCapture) {
+                    log_message!(
+                        LogLevel::Warning,
+                        &format!("enabling mouse capture failed: {}", e)
+                    );
+                }
+                self.year_heatmap_selected_date = chrono::Local::now().date_naive();
+                self.application_state = ApplicationState::YearHeatmap;
+            }
+            AppEvent::TodoListPopup | AppEvent::FilterSchedule => {}
+        }
+    }
+
+    fn is_panel_visible(&self, panel_id: PanelId) -> bool {
+        *self.panel_visibility.get(&panel_id).unwrap_or(&true)
+    }
+
+    fn toggle_panel_visibility(&mut self, panel_id: PanelId) {
+        let visible = self.is_panel_visible(panel_id);
+        self.panel_visibility.insert(panel_id, !visible);
+    }
+
+    /// Moves focus to the next visible panel, wrapping around after the last one
+    fn focus_next_panel(&mut self) {
+        let start_index = self.focused_panel.map_or(0, |focused_panel| {
+            TOGGLEABLE_PANELS
+                .iter()
+                .position(|panel_id| *panel_id == focused_panel)
+                .map_or(0, |index| index + 1)
+        });
+        self.focused_panel = (0..TOGGLEABLE_PANELS.len())
+            .map(|offset| TOGGLEABLE_PANELS[(start_index + offset) % TOGGLEABLE_PANELS.len()])
+            .find(|panel_id| self.is_panel_visible(*panel_id));
+    }
+
+    fn panel_border_style(&self, panel_id: PanelId) -> Style {
+        if self.focused_panel == Some(panel_id) {
+            Style::default().fg(Color::LightBlue)
+        } else {
+            Style::default()
+        }
+    }
+
+    /// Average temperature and precipitation of the forecast days with a session against the
+    /// ones without, from the first weather location
+    fn run_day_weather_lines(&self) -> Vec<Line<'static>> {
+        let Some((_, city_weather)) = self.city_weather.first() else {
+            return vec![Line::styled(
+                "no weather data",
+                Style::default().fg(Color::DarkGray),
+            )];
+        };
+        let run_days =
+            aggregate_weather_for_run_days(city_weather, &self.environment_dict.running_schedule);
+        let rest_days: Vec<CityWeather> = city_weather
+            .dates()
+            .into_iter()
+            .filter(|date| run_days.iter().all(|(run_date, _)| run_date != date))
+            .filter_map(|date| city_weather.for_date(date))
+            .collect();
+        let run_days: Vec<CityWeather> = run_days
+            .into_iter()
+            .map(|(_, day_weather)| day_weather)
+            .collect();
+        [("Run days", run_days), ("Rest days", rest_days)]
+            .into_iter()
+            .map(|(label, days)| {
+                let (temperature, precipitation) = average_temperature_and_precipitation(&days);
+                Line::styled(
+                    format!(
+                        "{:<10}{} days, {}, {}",
+                        label,
+                        days.len(),
+                        format_weather_value(temperature, "°C"),
+                        format_weather_value(precipitation, "mm")
+                    ),
+                    DEFAULT_TEXT_COLOR,
+                )
+            })
+            .collect()
+    }
+
+    /// One bar per workout type, most frequent first, with its share of all non rest sessions
+    fn workout_type_lines(&self) -> Vec<Line<'static>> {
+        let mut workout_counts: Vec<(ActivityType, u32)> =
+            count_workouts_by_type(&self.environment_dict.running_schedule)
+                .into_iter()
+                .collect();
+        let total_workouts: u32 = workout_counts.iter().map(|(_, count)| count).sum();
+        if total_workouts == 0 {
+            return vec![Line::styled(
+                "no workouts",
+                Style::default().fg(Color::DarkGray),
+            )];
+        }
+        // alphabetical on ties so the order doesn't depend on the hash map
+        workout_counts.sort_by(|a, b| {
+            b.1.cmp(&a.1)
+                .then_with(|| a.0.to_string().cmp(&b.0.to_string()))
+        });
+        let label_width = workout_counts
+            .iter()
+            .map(|(activity_type, _)| activity_type.to_string().chars().count())
+            .max()
+            .unwrap_or(0);
+        workout_counts
+            .into_iter()
+            .map(|(activity_type, count)| {
+                let share = count as f64 / total_workouts as f64;
+                let bar_length = (share * WORKOUT_TYPE_BAR_WIDTH as f64).round() as usize;
+                Line::from(vec![
+                    Span::styled(
+                        format!(
+                            "{:<width$} ",
+                            activity_type.to_string(),
+                            width = label_width
+                        ),
+                        DEFAULT_TEXT_COLOR,
+                    ),
+                    Span::styled(
+                        "█".repeat(bar_length),
+                        ActivityType::apply_style_to_activity(&activity_type),
+                    ),
+                    Span::styled(
+                        "░".repeat(WORKOUT_TYPE_BAR_WIDTH - bar_length),
+                        Style::default().fg(Color::DarkGray),
+                    ),
+                    Span::styled(
+                        format!(" {:>3.0}% ({})", share * 100.0, count),
+                        DEFAULT_TEXT_COLOR,
+                    ),
+                ])
+            })
+            .collect()
+    }
+
+    /// First date after today with a session that isn't `rest`, and that session, the am one
+    /// if both are
+    fn display_next_scheduled_run(&self) -> Option<(NaiveDate, ActivityType)> {
+        let today = chrono::Local::now().date_naive();
+        self.environment_dict
+            .running_schedule
+            .iter()
+            .filter_map(|run_entry| {
+                let date = run_entry.parsed_date().filter(|date| *date > today)?;
+                let activity_type = [&run_entry.am, &run_entry.pm]
+                    .into_iter()
+                    .flatten()
+                    .find(|session| **session != ActivityType::Rest)?;
+                Some((date, activity_type.clone()))
+            })
+            .min_by_key(|(date, _)| *date)
+    }
+
+    /// Repaints the top border of a main panel as a gradient when `ui.gradient_theme` is set
+    fn render_gradient_border(&self, block: Block, area: Rect, f: &mut Frame) {
+        if self.environment_dict.ui.gradient_theme.unwrap_or(false) {
+            f.render_widget(
+                render_border_with_gradient(block, GRADIENT_START_COLOR, GRADIENT_END_COLOR),
+                area,
+            );
+        }
+    }
+
+    /// Sun arc of `dt` in `area`, dawn and dusk come from the weather api for the Berlin clock
+    /// since it is queried in Europe/Berlin time, the other clocks use the defaults
+    fn render_time_of_day_indicator(&self, dt: &DateTime<Tz>, area: Rect, frame: &mut Frame) {
+        let (sunrise, sunset) = self
+            .city_weather
+            .first()
+            .filter(|_| dt.timezone() == chrono_tz::Europe::Berlin)
+            .and_then(|(_, city_weather)| city_weather.sun_times_on(dt.date_naive()))
+            .unwrap_or((DEFAULT_SUNRISE, DEFAULT_SUNSET));
+        frame.render_widget(
+            Paragraph::new(sun_arc_line(dt.time(), sunrise, sunset)),
+            area,
+        );
+    }
+
+    /// Moves the schedule day selection by `offset` columns, staying within the 7 shown days
+    fn select_schedule_day(&mut self, offset: isize) {
+        let selected_column = self.schedule_table_state.selected_column().unwrap_or(0) as isize;
+        self.schedule_table_state
+            .select_column(Some((selected_column + offset).clamp(1, 7) as usize));
+    }
+
+    fn selected_schedule_date(&self) -> Option<NaiveDate> {
+        self.schedule_table_state
+            .selected_column()
+            .filter(|column| *column > 0)
+            .map(|column| {
+                chrono::Local::now().date_naive() + chrono::Duration::days(column as i64 - 1)
+            })
+    }
+
+    fn render_todo_as_table(&mut self, items: &[TodoItem], area: Rect, frame: &mut Frame) {
+        let todo_list_title = if self.todo_sort_by_priority {
+            "TODO [priority]"
+        } else {
+            "TODO [order]"
+        };
+        let today = chrono::Local::now().date_naive();
+        let overdue_count = items
+            .iter()
+            .filter(|todo_item| {
+                !todo_item.done && todo_item.due_date.is_some_and(|due_date| due_date < today)
+            })
+            .count();
+        frame.render_stateful_widget(
+            todo_table(items, self.todo_sort_by_priority)
+                .row_highlight_style(Style::default().add_modifier(Modifier::REVERSED))
+                .block(
+                    render_block_with_title_and_subtitle(
+                        todo_list_title,
+                        &format!("{} items, {} overdue", items.len(), overdue_count),
+                    )
+                    .border_style(self.panel_border_style(PanelId::TodoList)),
+                ),
+            area,
+            &mut self.todo_table_state,
+        );
+        // tag chips over the right end of each visible task cell, below the header row
+        let table_area = Block::bordered().inner(area);
+        if items.is_empty() {
+            render_empty_state_placeholder(
+                Rect {
+                    y: table_area.y + 1,
+                    height: table_area.height.saturating_sub(1),
+                    ..table_area
+                },
+                if self.environment_dict.todo_list.is_empty() {
+                    "No todo items — press ctrl+t to add one"
+                } else {
+                    "No todo items match the tag filter"
+                },
+                frame,
+            );
+            return;
+        }
+        let [_, _, task_column, _] = Layout::horizontal(TODO_TABLE_WIDTHS)
+            .flex(Flex::Start)
+            .spacing(1)
+            .areas(table_area);
+        for (row_y, todo_item) in (table_area.y + 1..table_area.bottom())
+            .zip(items.iter().skip(self.todo_table_state.offset()))
+        {
+            if todo_item.tags.is_empty() {
+                continue;
+            }
+            let chips_width = todo_item
+                .tags
+                .iter()
+                .map(|tag| tag.chars().count() as u16 + 2 + TAG_CHIP_SPACING)
+                .sum::<u16>()
+                .min(task_column.width / 2);
+            render_todo_item_tags(
+                &todo_item.tags,
+                Rect {
+                    x: task_column.right() - chips_width,
+                    y: row_y,
+                    width: chips_width,
+                    height: 1,
+                },
+                frame,
+            );
+        }
+    }
+
+    /// Indices into `environment_dict.todo_list` of the rows the todo panel shows, in their
+    /// order, sorted by priority and filtered by tag if either is on
+    fn displayed_todo_indices(&self) -> Vec<usize> {
+        let todo_list = &self.environment_dict.todo_list;
+        let mut displayed_todo_indices: Vec<usize> = if self.todo_sort_by_priority {
+            todo_indices_by_priority(todo_list)
+        } else {
+            (0..todo_list.len()).collect()
+        };
+        if !self.todo_tags_filter.is_empty() {
+            displayed_todo_indices.retain(|index| {
+                todo_list[*index]
+                    .tags
+                    .iter()
+                    .any(|todo_tag| self.todo_tags_filter.contains(todo_tag))
+            });
+        }
+        displayed_todo_indices
+    }
+
+    /// Moves the selection by `offset` displayed rows, to the first row if nothing shown is
+    /// selected
+    fn select_todo_item(&mut self, offset: isize) {
+        let displayed_todo_indices = self.displayed_todo_indices();
+        if displayed_todo_indices.is_empty() {
+            self.selected_todo_index = None;
+            return;
+        }
+        let selected_row = self.selected_todo_index.and_then(|selected_index| {
+            displayed_todo_indices
+                .iter()
+                .position(|index| *index == selected_index)
+        });
+        let row = match selected_row {
+            Some(row) => row
+                .saturating_add_signed(offset)
+                .min(displayed_todo_indices.len() - 1),
+            None => 0,
+        };
+        self.selected_todo_index = Some(displayed_todo_indices[row]);
+    }
+
+    /// Swaps the selected item with the displayed row `offset` away, only in list order since
+    /// the priority sort would undo the move
+    fn move_selected_todo_item(&mut self, offset: isize) {
+        if self.todo_sort_by_priority {
+            self.status_message = "todo items can't be moved while sorted by priority".to_string();
+            return;
+        }
+        let Some(from) = self.selected_todo_index else {
+            return;
+        };
+        let displayed_todo_indices = self.displayed_todo_indices();
+        let Some(to) = displayed_todo_indices
+            .iter()
+            .position(|index| *index == from)
+            .and_then(|row| row.checked_add_signed(offset))
+            .and_then(|row| displayed_todo_indices.get(row).copied())
+        else {
+            return;
+        };
+        if self.refuse_write_in_read_only_mode() {
+            return;
+        }
+        reorder_todo_items(&mut self.environment_dict.todo_list, from, to);
+        self.selected_todo_index = Some(to);
+        if let Err(e) = self.write_environment_dict_to_json() {
+            self.status_message = format!("saving todo list failed: {}", e);
+        }
+    }
+
+    fn modify_todo_list_popup(&mut self) {
+        self.application_state = ApplicationState::InsertCalendarItemPopup;
+    }
+
+    /// Set running to false to quit the application.
+    fn quit(&mut self) {
+        self.running = false;
+    }
+
+    fn ui(&mut self, f: &mut Frame) {
+        #[cfg(debug_assertions)]
+        let frame_started_at = Instant::now();
+        /* #region layout */
+        let calendar_visible = self.is_panel_visible(PanelId::Calendar);
+        let running_schedule_visible = self.is_panel_visible(PanelId::RunningSchedule);
+        let shortcuts_visible = self.is_panel_visible(PanelId::Shortcuts);
+        let datetime_visible = self.is_panel_visible(PanelId::Datetime);
+        let todo_list_visible = self.is_panel_visible(PanelId::TodoList);
+        let bottom_middle_visible = shortcuts_visible || datetime_visible;
+        let left_bottom_visible = running_schedule_visible || bottom_middle_visible;
+
+        let layout_main = Layout::default()
+            .direction(Direction::Horizontal)
+            .margin(1)
             .constraints(vec![
                 Constraint::Percentage(VERTICAL_SPLIT_PERCENTAGE),
                 Constraint::Fill(1),
             ])
+            .split(f.area());
+        let layout_left_side = Layout::default()
+            .direction(Direction::Vertical)
+            .vertical_margin(0)
+            .spacing(0)
+            .constraints(vec![
+                panel_constraint(
+                    calendar_visible,
+                    left_bottom_visible,
+                    Constraint::Percentage(VERTICAL_SPLIT_PERCENTAGE),
+                ),
+                panel_constraint(left_bottom_visible, calendar_visible, Constraint::Fill(1)),
+            ])
             .split(layout_main[0]);
         let layout_left_bottom = Layout::default()
             .direction(Direction::Horizontal)
-            .constraints(vec![Constraint::Percentage(65), Constraint::Fill(1)])
+            .constraints(vec![
+                panel_constraint(
+                    running_schedule_visible,
+                    bottom_middle_visible,
+                    Constraint::Percentage(65),
+                ),
+                panel_constraint(
+                    bottom_middle_visible,
+                    running_schedule_visible,
+                    Constraint::Fill(1),
+                ),
+            ])
             .split(layout_left_side[1]);
         let layout_bottom_middle = Layout::default()
             .direction(Direction::Vertical)
-            .constraints(vec![Constraint::Percentage(60), Constraint::Fill(1)])
+            .constraints(vec![
+                panel_constraint(
+                    shortcuts_visible,
+                    datetime_visible,
+                    Constraint::Percentage(60),
+                ),
+                panel_constraint(datetime_visible, shortcuts_visible, Constraint::Fill(1)),
+            ])
             .split(layout_left_bottom[1]);
         let layout_right = Layout::default()
             .direction(Direction::Vertical)
             .constraints(vec![
-                Constraint::Percentage(VERTICAL_SPLIT_PERCENTAGE),
+                panel_constraint(
+                    todo_list_visible,
+                    true,
+                    Constraint::Percentage(VERTICAL_SPLIT_PERCENTAGE),
+                ),
                 Constraint::Fill(1),
             ])
             .split(layout_main[1]);
-        let layout_gauges = Layout::default()
-            .direction(Direction::Vertical)
-            .constraints(vec![
+        // year goes first and week last when the gauges don't fit
+        let layout_gauges = split_layout_with_min_size(
+            layout_right[1],
+            Direction::Vertical,
+            &[
                 Constraint::Fill(1),
                 Constraint::Fill(1),
                 Constraint::Fill(1),
-            ])
-            .split(layout_right[1]);
+            ],
+            &[GAUGE_MIN_HEIGHT; 3],
+            &[0, 1, 2],
+        );
+        /* #endregion */
+
+        /* #region datetime */
+        let utc_now = chrono::Utc::now();
+        let ohio_time: chrono::DateTime<Tz> = utc_now.with_timezone(&chrono_tz::US::Eastern);
+        let berlin_time: chrono::DateTime<Tz> = utc_now.with_timezone(&chrono_tz::Europe::Berlin);
+        let tokyo_time: chrono::DateTime<Tz> = utc_now.with_timezone(&chrono_tz::Asia::Tokyo);
+        let datetime_text: Vec<Line<'_>> = vec![
+            vec![Span::styled(
+                ohio_time.format("%Y-%m-%d %H:%M:%S").to_string()
+                    + " "
+                    + &ohio_time.timezone().to_string()
+                    + "     7°C  rain:80%",
+                Style::default().fg(Color::Yellow),
+            )]
+            .into(),
+            vec![Span::styled(
+                berlin_time.format("%Y-%m-%d %H:%M:%S").to_string()
+                    + " "
+                    + &berlin_time.timezone().to_string()
+                    + "  8°C  rain:50%",
+                Style::default().fg(Color::Yellow),
+            )]
+            .into(),
+            vec![Span::styled(
+                tokyo_time.format("%Y-%m-%d %H:%M:%S").to_string()
+                    + " "
+                    + &tokyo_time.timezone().to_string()
+                    + "     9°C  rain:0%",
+                Style::default().fg(Color::Yellow),
+            )]
+            .into(),
+        ];
+        /* #endregion */
+
+        /* #region todolist */
+        let displayed_todo_indices = self.displayed_todo_indices();
+        let selected_todo_row = self.selected_todo_index.and_then(|selected_index| {
+            displayed_todo_indices
+                .iter()
+                .position(|index| *index == selected_index)
+        });
+        self.todo_table_state.select(selected_todo_row);
+        let displayed_todo_items: Vec<TodoItem> = displayed_todo_indices
+            .into_iter()
+            .map(|index| self.environment_dict.todo_list[index].clone())
+            .collect();
+        /* #endregion */
+
+        /* #region running schedule */
+        let mut am_running_items: Vec<ActivityType> = vec![ActivityType::Rest; 7];
+        let mut pm_running_items: Vec<ActivityType> = vec![ActivityType::Rest; 7];
+        let mut duration_items: Vec<String> = vec!["-".to_string(); 7];
+        let mut calorie_items: Vec<String> = vec!["-".to_string(); 7];
+        let weight_kg = self.environment_dict.settings.weight_kg;
+        let mut debug_vector: Vec<&str> = vec![];
+        let current_date = chrono::Local::now().naive_local().date();
+        let date_to_index_map = build_date_to_index_map(current_date, 7);
+        // append_to_log(&format!("{:?}", date_to_index_map)).unwrap();
+        let running_schedule = self.get_running_schedule_from_json();
+        for run_entry in running_schedule.iter() {
+            if let Some(insertion_index) = date_to_index_map.get(&run_entry.date) {
+                if let Some(current_am_activity) = &run_entry.am {
+                    am_running_items[*insertion_index as usize] = current_am_activity.clone();
+                }
+                if let Some(current_pm_activity) = &run_entry.pm {
+                    pm_running_items[*insertion_index as usize] = current_pm_activity.clone();
+                }
+                if let Some(duration_seconds) = run_entry.duration_seconds {
+                    duration_items[*insertion_index as usize] = format_duration(duration_seconds);
+                }
+                if let (Some(weight_kg), Some(_)) = (weight_kg, run_entry.distance_km) {
+                    calorie_items[*insertion_index as usize] =
+                        format!("~{:.0} kcal", estimate_entry_calories(run_entry, weight_kg));
+                }
+            }
+        }
+        /* #endregion */
+
+        /* #region table */
+        let today = chrono::Local::now();
+        let weekday_index = today.weekday().num_days_from_monday() as usize;
+        let mut weekdays_array = vec![format!("W{}", week_number_for_date(current_date))];
+        for (day_increment, weekday_string) in weekday_strings()
+            .iter()
+            .cycle()
+            .skip(weekday_index)
+            .take(7)
+            .enumerate()
+        {
+            let date = current_date + chrono::Duration::days(day_increment as i64);
+            // the window crosses into the next week on its monday
+            if day_increment > 0 && date.weekday() == chrono::Weekday::Mon {
+                weekdays_array.push(format!(
+                    "W{} {}\n{}",
+                    week_number_for_date(date),
+                    weekday_string,
+                    format_date_relative(date)
+                ));
+            } else {
+                weekdays_array.push(format!(
+                    "{}\n{}",
+                    weekday_string,
+                    format_date_relative(date)
+                ));
+            }
+        }
+        let weekdays_array: [String; 8] = weekdays_array.try_into().expect("Incorrect array size");
+
+        let header = weekdays_array
+            .into_iter()
+            .map(Cell::from)
+            .collect::<Row>()
+            .style(HEADER_STYLE)
+            .height(SCHEDULE_HEADER_HEIGHT);
+
+        let session_count = am_running_items
+            .iter()
+            .chain(pm_running_items.iter())
+            .filter(|activity_type| **activity_type != ActivityType::Rest)
+            .count();
+        let schedule_window_distance = total_distance_for_date_range(
+            &running_schedule,
+            current_date,
+            current_date + chrono::Duration::days(6),
+        );
+        let mut am_running_items_table = vec![Cell::from("Training AM")];
+        am_running_items_table.extend(am_running_items.iter().map(activity_cell));
+        let mut pm_running_items_table = vec![Cell::from("Training PM")];
+        pm_running_items_table.extend(pm_running_items.iter().map(activity_cell));
+        let mut duration_items_table = vec!["Duration".to_string()];
+        duration_items_table.append(&mut duration_items);
+        let mut weather_items_table = vec!["Weather", "Sunny"];
+        weather_items_table.append(&mut debug_vector);
+        let mut feels_like_items_table = vec!["Feels like".to_string()];
+        for day_increment in 0..7 {
+            let noon_date = current_date + chrono::Duration::days(day_increment);
+            feels_like_items_table.push(
+                match self
+                    .hourly_forecast
+                    .as_ref()
+                    .and_then(|forecast| forecast.apparent_temperature_at_noon(noon_date))
+                {
+                    Some(apparent_temperature) => format!("{}°C", apparent_temperature.round()),
+                    None => "–".to_string(),
+                },
+            );
+        }
+        // daily range of the first weather location
+        let mut low_temperature_items_table = vec!["Low".to_string()];
+        let mut high_temperature_items_table = vec!["High".to_string()];
+        for day_increment in 0..7 {
+            let date = current_date + chrono::Duration::days(day_increment);
+            let (low_temperature, high_temperature) = self
+                .city_weather
+                .first()
+                .and_then(|(_, city_weather)| city_weather.temperature_range_on(date))
+                .unwrap_or_default();
+            low_temperature_items_table.push(format_weather_value(low_temperature, "°C"));
+            high_temperature_items_table.push(format_weather_value(high_temperature, "°C"));
+        }
+        let row_style = Style::default().fg(Color::Yellow);
+        let mut rows = vec![
+            Row::new(vec!["Dawn start", "7:12"]).style(row_style),
+            Row::new(vec!["Dawn end", "7:42"]).style(row_style),
+            Row::new(vec!["Dusk start", "20:12"]).style(row_style),
+            Row::new(vec!["Dusk end", "20:50"]).style(row_style),
+        ];
+        // a failed request leaves one blank row that the error gets drawn over
+        let weather_error_row_index = rows.len() as u16;
+        if self.weather_error.is_some() {
+            rows.push(Row::new(Vec::<Cell>::new()));
+        } else {
+            rows.extend([
+                Row::new(weather_items_table).style(row_style),
+                Row::new(low_temperature_items_table).style(row_style),
+                Row::new(high_temperature_items_table).style(row_style),
+                Row::new(feels_like_items_table).style(row_style),
+            ]);
+        }
+        rows.extend([
+            Row::new(am_running_items_table).style(row_style),
+            Row::new(pm_running_items_table).style(row_style),
+            Row::new(duration_items_table).style(row_style),
+        ]);
+        if weight_kg.is_some() {
+            let mut calorie_items_table = vec!["Calories".to_string()];
+            calorie_items_table.append(&mut calorie_items);
+            rows.push(Row::new(calorie_items_table).style(row_style));
+        }
+        let widths = [
+            Constraint::Length(14),
+            Constraint::Fill(1),
+            Constraint::Fill(1),
+            Constraint::Fill(1),
+            Constraint::Fill(1),
+            Constraint::Fill(1),
+            Constraint::Fill(1),
+            Constraint::Fill(1),
+        ];
+        let table_bottom_left = Table::new(rows, widths)
+            .header(header)
+            .highlight_symbol(Text::from(vec![
+                "".into(),
+                self.highlight_symbol.clone().into(),
+                self.highlight_symbol.clone().into(),
+                "".into(),
+            ]))
+            .highlight_spacing(self.schedule_highlight_spacing.clone())
+            .column_highlight_style(Style::default().add_modifier(Modifier::REVERSED));
         /* #endregion */
 
-        /* #region datetime */
-        let utc_now = chrono::Utc::now();
-        let ohio_time: chrono::DateTime<Tz> = utc_now.with_timezone(&chrono_tz::US::Eastern);
-        let berlin_time: chrono::DateTime<Tz> = utc_now.with_timezone(&chrono_tz::Europe::Berlin);
-        let tokyo_time: chrono::DateTime<Tz> = utc_now.with_timezone(&chrono_tz::Asia::Tokyo);
-        let datetime_text: Vec<Line<'_>> = vec![
-            vec![Span::styled(
-                ohio_time.format("%Y-%m-%d %H:%M:%S").to_string()
-                    + " "
-                    + &ohio_time.timezone().to_string()
-                    + "     7°C  rain:80%",
-                Style::default().fg(Color::Yellow),
-            )]
-            .into(),
-            vec![Span::styled(
-                berlin_time.format("%Y-%m-%d %H:%M:%S").to_string()
-                    + " "
-                    + &berlin_time.timezone().to_string()
-                    + "  8°C  rain:50%",
-                Style::default().fg(Color::Yellow),
-            )]
-            .into(),
-            vec![Span::styled(
-                tokyo_time.format("%Y-%m-%d %H:%M:%S").to_string()
-                    + " "
-                    + &tokyo_time.timezone().to_string()
-                    + "     9°C  rain:0%",
-                Style::default().fg(Color::Yellow),
-            )]
-            .into(),
-        ];
+        /* #region widgets */
+
+        let top_right_border_set = symbols::border::Set {
+            top_left: symbols::line::NORMAL.horizontal_down,
+            ..symbols::border::PLAIN
+        };
+        let collapsed_top_and_left_border_set = symbols::border::Set {
+            top_left: symbols::line::NORMAL.vertical_right,
+            top_right: symbols::line::NORMAL.vertical_left,
+            bottom_left: symbols::line::NORMAL.horizontal_up,
+            ..symbols::border::PLAIN
+        };
+        let rounded_totals: Vec<_> = self.running_totals.iter().map(|num| num.round()).collect();
+        let [week_current, month_current, year_current]: [f64; 3] = rounded_totals
+            .try_into()
+            .expect("Expected exactly 3 elements in running_totals");
+
+        let week_max = f64::max(DEFAULT_WEEKLY_GOAL_KM, week_current);
+        let month_max = f64::max(self.monthly_goal_km(), month_current);
+        let year_max = f64::max(DEFAULT_YEARLY_GOAL_KM, year_current);
+        let label_style_gauge = Style::default()
+            .fg(DEFAULT_TEXT_COLOR)
+            .add_modifier(Modifier::DIM);
+        let gauge_week = Gauge::default()
+            .gauge_style(GAUGE4_COLOR)
+            .ratio(week_current / week_max)
+            .label(Span::styled(
+                format!(
+                    "{}/{}",
+                    format_km_compact(week_current),
+                    format_km_compact(week_max)
+                ),
+                label_style_gauge,
+            ));
+        let gauge_month = Gauge::default()
+            .gauge_style(GAUGE4_COLOR)
+            .ratio(month_current / month_max)
+            .label(Span::styled(
+                format!(
+                    "{}/{}",
+                    format_km_compact(month_current),
+                    format_km_compact(month_max)
+                ),
+                label_style_gauge,
+            ));
+        let gauge_year = Gauge::default()
+            .gauge_style(GAUGE4_COLOR)
+            .ratio(year_current / year_max)
+            .label(Span::styled(
+                format!(
+                    "{}/{}",
+                    format_km_compact(year_current),
+                    format_km_compact(year_max)
+                ),
+                label_style_gauge,
+            ));
         /* #endregion */
 
-        /* #region todolist */
-        let mut todo_list_text: Vec<Line<'_>> = vec![Span::styled("TODO", HEADER_STYLE).into()];
-        if let Some(todo_items) = self.environment_dict["todo_list"].as_array() {
-            for (index, todo_item) in todo_items.iter().enumerate() {
-                if let Some(todo_item_inner) = todo_item.as_str() {
-                    todo_list_text.push(
-                        Span::styled("\t".to_string() + todo_item_inner, DEFAULT_TEXT_COLOR).into(),
+        /* #region status bar */
+        // last row of the screen, left free by the outer layout margin
+        let status_bar_area = Rect {
+            y: f.area().bottom().saturating_sub(1),
+            height: f.area().height.min(1),
+            ..f.area()
+        };
+        match self.application_state {
+            ApplicationState::CommandMode => {
+                let [command_prefix_area, command_input_area] =
+                    Layout::horizontal([Constraint::Length(1), Constraint::Fill(1)])
+                        .areas(status_bar_area);
+                self.command_textarea
+                    .set_style(Style::default().fg(DEFAULT_TEXT_COLOR));
+                self.command_textarea
+                    .set_cursor_line_style(Style::default());
+                f.render_widget(Span::styled(":", DEFAULT_TEXT_COLOR), command_prefix_area);
+                f.render_widget(&self.command_textarea, command_input_area);
+            }
+            _ => f.render_widget(
+                Line::from(vec![
+                    Span::styled(
+                        format!(" {} ", describe_application_state(&self.application_state)),
+                        Style::default().fg(Color::Black).bg(Color::LightBlue),
+                    ),
+                    Span::styled(
+                        if self.read_only { " READ-ONLY " } else { "" },
+                        Style::default().fg(Color::Black).bg(Color::Red),
+                    ),
+                    Span::styled(
+                        match (self.has_unsaved_changes, self.last_save_time) {
+                            (false, _) => String::new(),
+                            (true, None) => " ● unsaved".to_string(),
+                            (true, Some(last_save_time)) => format!(
+                                " ● unsaved, last saved {}s ago",
+                                last_save_time.elapsed().as_secs()
+                            ),
+                        },
+                        Style::default().fg(Color::Yellow),
+                    ),
+                    Span::styled(
+                        " ".to_string() + &self.status_message,
+                        if self
+                            .status_flash_until
+                            .is_some_and(|flash_until| Instant::now() < flash_until)
+                        {
+                            Style::default().fg(Color::Black).bg(DEFAULT_TEXT_COLOR)
+                        } else {
+                            Style::default().fg(DEFAULT_TEXT_COLOR)
+                        },
+                    ),
+                ]),
+                status_bar_area,
+            ),
+        }
+        /* #endregion */
+
+        let recommendation = recommend_rest_or_run(
+            &self.environment_dict.running_schedule,
+            current_streak(&self.environment_dict.running_schedule),
+            rolling_average_km(
+                &self.environment_dict.running_schedule,
+                ACUTE_TRAINING_LOAD_DAYS,
+            ),
+            &self
+                .environment_dict
+                .settings
+                .fatigue_thresholds
+                .unwrap_or_default(),
+        );
+        let recommendation_color = match recommendation {
+            "Rest today" => Color::Red,
+            "Easy run" => Color::Yellow,
+            _ => Color::Green,
+        };
+
+        /* #region rendering */
+        let shortcut_block = Block::new()
+            .title(
+                Line::from(Span::styled(
+                    recommendation,
+                    Style::default()
+                        .fg(recommendation_color)
+                        .add_modifier(Modifier::BOLD),
+                ))
+                .right_aligned(),
+            )
+            .borders(Borders::TOP | Borders::RIGHT | Borders::LEFT)
+            .border_set(top_right_border_set)
+            .border_style(self.panel_border_style(PanelId::Shortcuts));
+        f.render_widget(
+            self.shortcut_list_text_block
+                .clone()
+                .block(shortcut_block.clone()),
+            layout_bottom_middle[0],
+        );
+        self.render_gradient_border(shortcut_block, layout_bottom_middle[0], f);
+        let calendar_block = Block::new()
+            .borders(Borders::LEFT | Borders::RIGHT | Borders::TOP | Borders::BOTTOM)
+            .border_style(self.panel_border_style(PanelId::Calendar))
+            .title(Span::styled("Next Scheduled Run", HEADER_STYLE));
+        let next_run_lines: Vec<Line> = match self.display_next_scheduled_run() {
+            Some((date, activity_type)) => {
+                let mut next_run_lines = vec![Line::from(vec![
+                    Span::styled(
+                        format!("{}: ", format_date_relative(date)),
+                        DEFAULT_TEXT_COLOR,
+                    ),
+                    Span::styled(
+                        activity_type.to_string(),
+                        ActivityType::apply_style_to_activity(&activity_type),
+                    ),
+                ])];
+                if let Some(target_km) = self
+                    .environment_dict
+                    .running_schedule
+                    .iter()
+                    .find(|run_entry| run_entry.parsed_date() == Some(date))
+                    .and_then(|run_entry| run_entry.distance_km)
+                    .filter(|distance_km| *distance_km > 0.0)
+                {
+                    next_run_lines.push(Line::styled(
+                        format!("Target {:.1} km", target_km),
+                        DEFAULT_TEXT_COLOR,
+                    ));
+                }
+                next_run_lines
+            }
+            None => vec![Line::styled(
+                "No runs scheduled — add one with ctrl+r",
+                Style::default().fg(Color::DarkGray),
+            )],
+        };
+        f.render_widget(
+            Paragraph::new(next_run_lines).block(calendar_block.clone()),
+            layout_left_side[0],
+        );
+        self.render_gradient_border(calendar_block, layout_left_side[0], f);
+        if self.weather_forecast_panel_active {
+            f.render_widget(
+                WeatherForecastPanel::new(
+                    self.hourly_forecast
+                        .as_ref()
+                        .map_or(vec![], |hourly_forecast| hourly_forecast.daily_summaries()),
+                )
+                .block(
+                    Block::new()
+                        .borders(Borders::ALL)
+                        .border_style(self.panel_border_style(PanelId::TodoList)),
+                ),
+                layout_right[0],
+            );
+        } else {
+            self.render_todo_as_table(&displayed_todo_items, layout_right[0], f);
+        }
+        let schedule_block = render_block_with_title_and_subtitle(
+            "Schedule",
+            &format!(
+                "{} sessions, {:.1} km",
+                session_count, schedule_window_distance
+            ),
+        )
+        .borders(Borders::TOP | Borders::LEFT | Borders::BOTTOM)
+        .border_style(self.panel_border_style(PanelId::RunningSchedule));
+        let schedule_block = match days_since_last_run(&self.environment_dict.running_schedule) {
+            Some(days @ 1..) => schedule_block.title(
+                Line::from(Span::styled(
+                    format!(
+                        "Last run: {} day{} ago",
+                        days,
+                        if days == 1 { "" } else { "s" }
+                    ),
+                    Style::default().fg(if days >= LAST_RUN_WARN_DAYS {
+                        Color::Red
+                    } else {
+                        Color::Yellow
+                    }),
+                ))
+                .centered(),
+            ),
+            _ => schedule_block,
+        };
+        if self.environment_dict.running_schedule.is_empty() {
+            f.render_widget(schedule_block.clone(), layout_left_bottom[0]);
+            render_empty_state_placeholder(
+                schedule_block.inner(layout_left_bottom[0]),
+                "No schedule entries — press ctrl+r to add a run",
+                f,
+            );
+        } else {
+            f.render_stateful_widget(
+                table_bottom_left.block(schedule_block.clone()),
+                layout_left_bottom[0],
+                &mut self.schedule_table_state,
+            );
+        }
+        self.render_gradient_border(schedule_block, layout_left_bottom[0], f);
+        if let Some(weather_error) = &self.weather_error {
+            let last_success_text = match self.last_weather_fetch_success {
+                Some(last_success) => format!(
+                    "last success {}",
+                    last_success.format(WEATHER_ERROR_TIME_FORMAT)
+                ),
+                None => "no successful fetch yet".to_string(),
+            };
+            // right of the left border, below the top border and the header
+            let weather_error_area = Rect {
+                x: layout_left_bottom[0].x + 1,
+                y: layout_left_bottom[0].y + 1 + SCHEDULE_HEADER_HEIGHT + weather_error_row_index,
+                width: layout_left_bottom[0].width.saturating_sub(1),
+                height: 1,
+            };
+            if weather_error_area.y < layout_left_bottom[0].bottom().saturating_sub(1) {
+                display_weather_error_panel(
+                    &format!("{} ({})", weather_error, last_success_text),
+                    weather_error_area,
+                    f,
+                );
+            }
+        }
+        // blocks are drawn separately so the milestone ticks line up with the bar
+        let rest_day_count = count_rest_days_in_week(
+            &self.environment_dict.running_schedule,
+            self.environment_dict
+                .settings
+                .week_start
+                .unwrap_or_default(),
+        );
+        let stale_days = self
+            .elapsed_since_last_update()
+            .map(|elapsed| elapsed.as_secs() / 86400)
+            .filter(|days| {
+                *days
+                    >= self
+                        .environment_dict
+                        .settings
+                        .staleness_warn_days
+                        .unwrap_or(DEFAULT_STALENESS_WARN_DAYS) as u64
+            });
+        let training_monotony = compute_training_monotony(
+            &self.environment_dict.running_schedule,
+            TRAINING_MONOTONY_WINDOW_DAYS,
+        );
+        let training_monotony_style = if training_monotony > TRAINING_MONOTONY_WARN_THRESHOLD {
+            label_style_gauge.fg(Color::Red)
+        } else {
+            label_style_gauge
+        };
+        let week_over_week_change = compute_week_over_week_change(
+            &self.environment_dict.running_schedule,
+            self.environment_dict
+                .settings
+                .week_start
+                .unwrap_or_default(),
+        );
+        let week_gauge_title = Line::from(vec![
+            Span::styled(format!("{} rest days, ", rest_day_count), label_style_gauge),
+            Span::styled(
+                format!("monotony {:.1}, ", training_monotony),
+                training_monotony_style,
+            ),
+            Span::styled(
+                format!("{:+.0}%", week_over_week_change),
+                label_style_gauge.fg(if week_over_week_change < 0.0 {
+                    Color::Red
+                } else {
+                    Color::Green
+                }),
+            ),
+        ]);
+        // chronic minus acute load, positive while fresh and negative while fatigued
+        let chronic_training_load = rolling_average_km(
+            &self.environment_dict.running_schedule,
+            CHRONIC_TRAINING_LOAD_DAYS,
+        );
+        let training_form = (chronic_training_load > 0.0).then(|| {
+            chronic_training_load
+                - rolling_average_km(
+                    &self.environment_dict.running_schedule,
+                    ACUTE_TRAINING_LOAD_DAYS,
+                )
+        });
+        let month_gauge_title = Line::from(Span::styled(
+            match training_form {
+                Some(training_form) => format!("Form: {:+.1}", training_form),
+                None => "Form: -".to_string(),
+            },
+            label_style_gauge.fg(match training_form {
+                Some(training_form) if training_form < 0.0 => Color::Red,
+                Some(_) => Color::Green,
+                None => Color::Gray,
+            }),
+        ));
+        let projected_year_km = project_yearly_total(
+            &self.environment_dict.running_schedule,
+            self.running_totals[2],
+        );
+        let year_projection_line = Line::from(Span::styled(
+            format!("Projected: {:.0} km", projected_year_km),
+            label_style_gauge.fg(if projected_year_km >= year_max {
+                Color::Green
+            } else {
+                Color::Red
+            }),
+        ))
+        .centered();
+        for (
+            gauge,
+            gauge_current,
+            gauge_max,
+            gauge_area,
+            gauge_name,
+            gauge_title,
+            gauge_bottom_title,
+        ) in [
+            (
+                gauge_week,
+                week_current,
+                week_max,
+                layout_gauges[0],
+                "Week",
+                week_gauge_title,
+                None,
+            ),
+            (
+                gauge_month,
+                month_current,
+                month_max,
+                layout_gauges[1],
+                "Month",
+                month_gauge_title,
+                None,
+            ),
+            (
+                gauge_year,
+                year_current,
+                year_max,
+                layout_gauges[2],
+                "Year",
+                Line::from(Span::styled(
+                    format!(
+                        "{} wk {}",
+                        SPARKLINE_WEEKS,
+                        render_running_schedule_as_sparkline(
+                            &self.environment_dict.running_schedule,
+                            SPARKLINE_WEEKS
+                        )
+                    ),
+                    label_style_gauge,
+                )),
+                Some(year_projection_line),
+            ),
+        ] {
+            // distance left to the next milestone on this gauge
+            let next_milestone_text = self
+                .environment_dict
+                .running_goals
+                .milestones
+                .iter()
+                .filter(|milestone| **milestone > gauge_current && **milestone <= gauge_max)
+                .min_by(|a, b| a.total_cmp(b))
+                .map_or(String::new(), |milestone| {
+                    format!("{:.0} km to {:.0}", milestone - gauge_current, milestone)
+                });
+            let mut gauge_block =
+                render_block_with_title_and_subtitle(gauge_name, &next_milestone_text)
+                    .title(gauge_title);
+            if let Some(gauge_bottom_title) = gauge_bottom_title {
+                gauge_block = gauge_block.title_bottom(gauge_bottom_title);
+            }
+            if let Some(stale_days) = stale_days {
+                gauge_block = gauge_block
+                    .border_set(STALE_GAUGE_BORDER_SET)
+                    .border_style(
+                        Style::default()
+                            .fg(Color::Yellow)
+                            .add_modifier(Modifier::DIM),
+                    )
+                    .title(
+                        Line::styled(
+                            format!("⚠ {} days stale", stale_days),
+                            Style::default().fg(Color::Yellow),
+                        )
+                        .right_aligned(),
+                    );
+            }
+            let milestone_ratios: Vec<f64> = self
+                .environment_dict
+                .running_goals
+                .milestones
+                .iter()
+                .map(|milestone| milestone / gauge_max)
+                .collect();
+            f.render_widget(
+                render_gauge_with_milestone_markers(gauge, &milestone_ratios),
+                gauge_block.inner(gauge_area),
+            );
+            f.render_widget(gauge_block, gauge_area);
+        }
+        let season = get_current_season(
+            self.environment_dict
+                .settings
+                .hemisphere
+                .unwrap_or_default(),
+        );
+        let datetime_block = Block::new()
+            .borders(Borders::ALL)
+            .border_set(collapsed_top_and_left_border_set)
+            .border_style(self.panel_border_style(PanelId::Datetime))
+            .title(format!(
+                "{} {} {} (full {})",
+                season.icon(),
+                current_date.format("%Y-%m-%d"),
+                moon_phase_icon(current_date),
+                next_full_moon().format("%m-%d")
+            ));
+        let datetime_text_width = datetime_text.iter().map(Line::width).max().unwrap_or(0);
+        f.render_widget(
+            Paragraph::new(datetime_text).block(datetime_block.clone()),
+            layout_bottom_middle[1],
+        );
+        // the arcs go right of the clock lines and are left out when the panel is too narrow
+        let datetime_inner_area = datetime_block.inner(layout_bottom_middle[1]);
+        if datetime_inner_area.width as usize > datetime_text_width + SUN_ARC_WIDTH {
+            for (row, zone_time) in [ohio_time, berlin_time, tokyo_time].iter().enumerate() {
+                if row as u16 >= datetime_inner_area.height {
+                    break;
+                }
+                let arc_area = Rect {
+                    x: datetime_inner_area.right() - SUN_ARC_WIDTH as u16,
+                    y: datetime_inner_area.y + row as u16,
+                    width: SUN_ARC_WIDTH as u16,
+                    height: 1,
+                };
+                self.render_time_of_day_indicator(zone_time, arc_area, f);
+            }
+        }
+        self.render_gradient_border(datetime_block, layout_bottom_middle[1], f);
+        match self.application_state {
+            ApplicationState::InsertRunPopup | ApplicationState::AddToRunningTotals => {
+                self.textarea_widget.set_block(
+                    Block::default()
+                        .borders(Borders::ALL)
+                        .border_style(Style::default().fg(Color::LightBlue))
+                        .title(self.popup_title("Running Input")),
+                );
+                self.textarea_widget
+                    .set_style(Style::default().fg(Color::Yellow));
+                self.textarea_widget.set_placeholder_style(Style::default());
+                self.textarea_widget.set_placeholder_text("prompt message");
+                render_centered_popup(
+                    f,
+                    f.area(),
+                    Constraint::Percentage(20),
+                    Constraint::Length(3), // top and bottom border + content
+                    &self.textarea_widget,
+                );
+            }
+            ApplicationState::InsertTodoItemPopup => {
+                self.textarea_widget.set_block(
+                    Block::default()
+                        .borders(Borders::ALL)
+                        .border_style(Style::default().fg(Color::LightBlue))
+                        .title(self.popup_title("New Todo Item")),
+                );
+                self.textarea_widget
+                    .set_style(Style::default().fg(Color::Yellow));
+                self.textarea_widget.set_placeholder_text("buy new shoes");
+                render_centered_popup(
+                    f,
+                    f.area(),
+                    Constraint::Percentage(30),
+                    Constraint::Length(3), // top and bottom border + content
+                    &self.textarea_widget,
+                );
+            }
+            ApplicationState::ApiKeyPrompt => {
+                self.textarea_widget.set_block(
+                    Block::default()
+                        .borders(Borders::ALL)
+                        .border_style(Style::default().fg(Color::LightBlue))
+                        .title(self.popup_title("Weather API Key (esc to skip)")),
+                );
+                self.textarea_widget
+                    .set_style(Style::default().fg(Color::Yellow));
+                self.textarea_widget.set_mask_char('\u{2022}');
+                self.textarea_widget
+                    .set_placeholder_text("key from geocode.maps.co");
+                render_centered_popup(
+                    f,
+                    f.area(),
+                    Constraint::Percentage(40),
+                    Constraint::Length(3), // top and bottom border + content
+                    &self.textarea_widget,
+                );
+            }
+            ApplicationState::YearHeatmap => {
+                let year = self.year_heatmap_selected_date.year();
+                let popup_area = center_the_popup_area(
+                    f.area(),
+                    Constraint::Length(HEATMAP_WEEKS * HEATMAP_CELL_WIDTH + 2),
+                    // top and bottom border + grid + status line
+                    Constraint::Length(HEATMAP_DAYS_PER_WEEK + 3),
+                );
+                let popup_block = Block::default()
+                    .borders(Borders::ALL)
+                    .border_style(Style::default().fg(Color::LightBlue))
+                    .title(format!(
+                        "{} (arrows or mouse select a day, esc closes)",
+                        year
+                    ));
+                let [grid_area, status_area] = Layout::vertical([
+                    Constraint::Length(HEATMAP_DAYS_PER_WEEK),
+                    Constraint::Length(1),
+                ])
+                .areas(popup_block.inner(popup_area));
+                f.render_widget(Clear, popup_area);
+                f.render_widget(popup_block, popup_area);
+                render_calendar_heatmap(
+                    &self.environment_dict.running_schedule,
+                    year,
+                    f,
+                    grid_area,
+                );
+                if let Some(selected_cell_area) =
+                    heatmap_cell_area(self.year_heatmap_selected_date, grid_area)
+                {
+                    f.buffer_mut().set_style(
+                        selected_cell_area,
+                        Style::default().add_modifier(Modifier::REVERSED),
+                    );
+                }
+                let selected_distance_km = total_distance_for_date_range(
+                    &self.environment_dict.running_schedule,
+                    self.year_heatmap_selected_date,
+                    self.year_heatmap_selected_date,
+                );
+                f.render_widget(
+                    Paragraph::new(format!(
+                        "{}  {:.1} km",
+                        self.year_heatmap_selected_date.format("%a %Y-%m-%d"),
+                        selected_distance_km
+                    ))
+                    .style(Style::default().fg(DEFAULT_TEXT_COLOR)),
+                    status_area,
+                );
+                self.year_heatmap_area = grid_area;
+            }
+            ApplicationState::ConfigureWeatherLocations => {
+                let popup_area = center_the_popup_area(
+                    f.area(),
+                    Constraint::Percentage(40),
+                    Constraint::Percentage(40),
+                );
+                f.render_widget(Clear, popup_area);
+                let [list_area, input_area] = Layout::vertical([
+                    Constraint::Fill(1),
+                    Constraint::Length(if self.weather_location_input.is_some() {
+                        3
+                    } else {
+                        0
+                    }),
+                ])
+                .areas(popup_area);
+                let location_list = List::new(
+                    self.weather_locations_draft
+                        .iter()
+                        .map(|weather_location| weather_location.to_string()),
+                )
+                .style(Style::default().fg(DEFAULT_TEXT_COLOR))
+                .highlight_style(Style::default().add_modifier(Modifier::REVERSED))
+                .block(
+                    Block::default()
+                        .borders(Borders::ALL)
+                        .border_style(Style::default().fg(Color::LightBlue))
+                        .title(self.popup_title("Weather Locations (a/e/d, s to save)")),
+                );
+                f.render_stateful_widget(
+                    location_list,
+                    list_area,
+                    &mut self.weather_locations_list_state,
+                );
+                if self.weather_location_input.is_some() {
+                    self.textarea_widget.set_block(
+                        Block::default()
+                            .borders(Borders::ALL)
+                            .border_style(Style::default().fg(Color::LightBlue))
+                            .title("Location"),
+                    );
+                    self.textarea_widget
+                        .set_style(Style::default().fg(Color::Yellow));
+                    self.textarea_widget
+                        .set_placeholder_text("Jena, 50.93, 11.59");
+                    f.render_widget(&self.textarea_widget, input_area);
+                }
+            }
+            ApplicationState::CopyEntryToDate(ref date_string) => {
+                self.textarea_widget.set_block(
+                    Block::default()
+                        .borders(Borders::ALL)
+                        .border_style(Style::default().fg(Color::LightBlue))
+                        .title(self.popup_title(&format!("Copy {} To", date_string))),
+                );
+                self.textarea_widget
+                    .set_style(Style::default().fg(Color::Yellow));
+                self.textarea_widget.set_placeholder_text("MM/DD/YYYY");
+                render_centered_popup(
+                    f,
+                    f.area(),
+                    Constraint::Percentage(20),
+                    Constraint::Length(3), // top and bottom border + content
+                    &self.textarea_widget,
+                );
+            }
+            ApplicationState::ConfirmDeleteEntry(ref date_string) => {
+                render_centered_popup(
+                    f,
+                    f.area(),
+                    Constraint::Percentage(30),
+                    Constraint::Length(3), // top and bottom border + content
+                    Paragraph::new(format!("Delete entry for {}? (y/n)", date_string))
+                        .style(Style::default().fg(DEFAULT_TEXT_COLOR))
+                        .block(
+                            Block::default()
+                                .borders(Borders::ALL)
+                                .border_style(Style::default().fg(Color::LightBlue))
+                                .title(self.popup_title("Confirm")),
+                        ),
+                );
+            }
+            ApplicationState::ConfirmClearLog => {
+                render_centered_popup(
+                    f,
+                    f.area(),
+                    Constraint::Percentage(20),
+                    Constraint::Length(3), // top and bottom border + content
+                    Paragraph::new("Clear log file? (y/n)")
+                        .style(Style::default().fg(DEFAULT_TEXT_COLOR))
+                        .block(
+                            Block::default()
+                                .borders(Borders::ALL)
+                                .border_style(Style::default().fg(Color::LightBlue))
+                                .title(self.popup_title("Confirm")),
+                        ),
+                );
+            }
+            ApplicationState::SetCountdown => {
+                self.textarea_widget.set_block(
+                    Block::default()
+                        .borders(Borders::ALL)
+                        .border_style(Style::default().fg(Color::LightBlue))
+                        .title("Countdown To"),
+                );
+                self.textarea_widget
+                    .set_style(Style::default().fg(Color::Yellow));
+                self.textarea_widget.set_placeholder_text("HH:MM");
+                render_centered_popup(
+                    f,
+                    f.area(),
+                    Constraint::Percentage(20),
+                    Constraint::Length(3), // top and bottom border + content
+                    &self.textarea_widget,
+                );
+            }
+            ApplicationState::Countdown => {
+                let remaining_seconds = self.countdown_target.map_or(0, |countdown_target| {
+                    (countdown_target - chrono::Local::now().naive_local())
+                        .num_seconds()
+                        .max(0) as u64
+                });
+                let countdown_lines: Vec<Line> =
+                    big_text_lines(&format_duration(remaining_seconds))
+                        .into_iter()
+                        .map(Line::from)
+                        .collect();
+                render_centered_popup(
+                    f,
+                    f.area(),
+                    Constraint::Length(40),
+                    Constraint::Length(countdown_lines.len() as u16 + 2), // top and bottom border + content
+                    Paragraph::new(countdown_lines)
+                        .alignment(ratatui::layout::Alignment::Center)
+                        .style(Style::default().fg(DEFAULT_TEXT_COLOR))
+                        .block(
+                            Block::default()
+                                .borders(Borders::ALL)
+                                .border_style(Style::default().fg(Color::LightBlue))
+                                .title(self.countdown_target.map_or(
+                                    "Countdown".to_string(),
+                                    |countdown_target| {
+                                        format!(
+                                            "Countdown to {}",
+                                            countdown_target.format(COUNTDOWN_TIME_FORMAT)
+                                        )
+                                    },
+                                )),
+                        ),
+                );
+            }
+            ApplicationState::Stopwatch => {
+                let stopwatch_lines: Vec<Line> =
+                    big_text_lines(&format_stopwatch(self.stopwatch_elapsed()))
+                        .into_iter()
+                        .map(Line::from)
+                        .collect();
+                render_centered_popup(
+                    f,
+                    f.area(),
+                    Constraint::Length(45),
+                    Constraint::Length(stopwatch_lines.len() as u16 + 2), // top and bottom border + content
+                    Paragraph::new(stopwatch_lines)
+                        .alignment(ratatui::layout::Alignment::Center)
+                        .style(Style::default().fg(DEFAULT_TEXT_COLOR))
+                        .block(
+                            Block::default()
+                                .borders(Borders::ALL)
+                                .border_style(Style::default().fg(Color::LightBlue))
+                                .title("Stopwatch (space start/pause, r reset, l log)"),
+                        ),
+                );
+            }
+            ApplicationState::TodoListPopup => {
+                render_centered_popup(
+                    f,
+                    f.area(),
+                    Constraint::Percentage(40),
+                    // top and bottom border + header + content
+                    Constraint::Length(displayed_todo_items.len() as u16 + 3),
+                    todo_table(&displayed_todo_items, self.todo_sort_by_priority).block(
+                        Block::default()
+                            .borders(Borders::ALL)
+                            .border_style(Style::default().fg(Color::LightBlue))
+                            .title("Todo List (any key to close)"),
+                    ),
+                );
+            }
+            ApplicationState::ScheduleFilterPrompt => {
+                self.textarea_widget.set_block(
+                    Block::default()
+                        .borders(Borders::ALL)
+                        .border_style(Style::default().fg(Color::LightBlue))
+                        .title("Filter Schedule By Session"),
+                );
+                self.textarea_widget
+                    .set_style(Style::default().fg(Color::Yellow));
+                self.textarea_widget
+                    .set_placeholder_text("tempo, long, ...");
+                render_centered_popup(
+                    f,
+                    f.area(),
+                    Constraint::Percentage(20),
+                    Constraint::Length(3), // top and bottom border + content
+                    &self.textarea_widget,
+                );
+            }
+            ApplicationState::FilteredScheduleView(ref session_type) => {
+                let highlight_style = Style::default()
+                    .fg(Color::LightBlue)
+                    .add_modifier(Modifier::BOLD);
+                let filtered_rows: Vec<Row> = filter_schedule_by_session_type(
+                    &self.environment_dict.running_schedule,
+                    session_type,
+                )
+                .into_iter()
+                .map(|run_entry| {
+                    let am_activity = run_entry.am.clone().unwrap_or_default();
+                    let pm_activity = run_entry.pm.clone().unwrap_or_default();
+                    Row::new(vec![
+                        Cell::from(
+                            run_entry
+                                .parsed_date()
+                                .map_or(run_entry.date.clone(), format_date_relative),
+                        ),
+                        Cell::from(highlight_matches(
+                            &am_activity.to_string(),
+                            session_type,
+                            highlight_style,
+                        ))
+                        .style(ActivityType::apply_style_to_activity(&am_activity)),
+                        Cell::from(highlight_matches(
+                            &pm_activity.to_string(),
+                            session_type,
+                            highlight_style,
+                        ))
+                        .style(ActivityType::apply_style_to_activity(&pm_activity)),
+                        Cell::from(
+                            run_entry
+                                .distance_km
+                                .map_or("-".to_string(), |distance_km| {
+                                    format!("{:.1} km", distance_km)
+                                }),
+                        ),
+                    ])
+                    .style(row_style)
+                })
+                .collect();
+                let filtered_table = Table::new(
+                    filtered_rows,
+                    [
+                        Constraint::Length(10),
+                        Constraint::Fill(1),
+                        Constraint::Fill(1),
+                        Constraint::Length(10),
+                    ],
+                )
+                .header(Row::new(vec!["Date", "AM", "PM", "Distance"]).style(HEADER_STYLE))
+                .row_highlight_style(Style::default().add_modifier(Modifier::REVERSED))
+                .block(
+                    Block::default()
+                        .borders(Borders::ALL)
+                        .border_style(Style::default().fg(Color::LightBlue))
+                        .title(format!("Sessions matching \"{}\"", session_type)),
+                );
+                render_centered_stateful_popup(
+                    f,
+                    f.area(),
+                    Constraint::Percentage(80),
+                    // leaves the status bar visible
+                    Constraint::Length(f.area().height.saturating_sub(1)),
+                    filtered_table,
+                    &mut self.filtered_schedule_table_state,
+                );
+            }
+            ApplicationState::TagFilter => {
+                let todo_tags = distinct_todo_tags(&self.environment_dict.todo_list);
+                let tag_list = List::new(todo_tags.iter().map(|todo_tag| {
+                    if self.todo_tags_filter.contains(todo_tag) {
+                        format!("[x] {}", todo_tag)
+                    } else {
+                        format!("[ ] {}", todo_tag)
+                    }
+                }))
+                .style(Style::default().fg(DEFAULT_TEXT_COLOR))
+                .highlight_style(Style::default().add_modifier(Modifier::REVERSED))
+                .block(
+                    Block::default()
+                        .borders(Borders::ALL)
+                        .border_style(Style::default().fg(Color::LightBlue))
+                        .title("Filter Todo Tags (space to toggle)"),
+                );
+                render_centered_stateful_popup(
+                    f,
+                    f.area(),
+                    Constraint::Percentage(30),
+                    Constraint::Length(todo_tags.len().max(1) as u16 + 2), // top and bottom border + content
+                    tag_list,
+                    &mut self.tag_filter_list_state,
+                );
+            }
+            ApplicationState::DuplicateEntryWarning(ref duplicate_dates) => {
+                let mut duplicate_lines = vec![
+                    Line::styled(
+                        "Only one entry per date is shown, merge or delete the others:",
+                        DEFAULT_TEXT_COLOR,
+                    ),
+                    Line::default(),
+                ];
+                duplicate_lines.extend(duplicate_dates.iter().map(|duplicate_date| {
+                    Line::styled(
+                        duplicate_date.format(RUN_ENTRY_DATE_FORMAT).to_string(),
+                        Style::default().fg(Color::Red),
+                    )
+                }));
+                render_centered_popup(
+                    f,
+                    f.area(),
+                    Constraint::Percentage(50),
+                    Constraint::Percentage(40),
+                    Paragraph::new(duplicate_lines).block(
+                        Block::default()
+                            .borders(Borders::ALL)
+                            .border_style(Style::default().fg(Color::LightBlue))
+                            .title("Duplicate Schedule Dates (enter to continue)"),
+                    ),
+                );
+            }
+            ApplicationState::ValidationWarnings(ref validation_warnings) => {
+                let warning_list = List::new(validation_warnings.iter().map(String::as_str))
+                    .style(Style::default().fg(DEFAULT_TEXT_COLOR))
+                    .highlight_style(Style::default().add_modifier(Modifier::REVERSED))
+                    .block(
+                        Block::default()
+                            .borders(Borders::ALL)
+                            .border_style(Style::default().fg(Color::LightBlue))
+                            .title("Data Warnings (enter to continue)"),
                     );
+                render_centered_stateful_popup(
+                    f,
+                    f.area(),
+                    Constraint::Percentage(60),
+                    Constraint::Percentage(50),
+                    warning_list,
+                    &mut self.validation_warnings_list_state,
+                );
+            }
+            ApplicationState::NewProfile => {
+                self.textarea_widget
+                    .set_style(Style::default().fg(Color::Yellow));
+                self.textarea_widget
+                    .set_placeholder_text("base, build, peak, ...");
+                let new_profile_title = self.popup_title("New Profile");
+                let form_fields = [FormField {
+                    label: &new_profile_title,
+                    textarea: self.textarea_widget.clone(),
+                    validator: Some(profile_name_error),
+                }];
+                render_form(
+                    f,
+                    center_the_popup_area(
+                        f.area(),
+                        Constraint::Percentage(30),
+                        Constraint::Length(FORM_FIELD_HEIGHT * form_fields.len() as u16),
+                    ),
+                    &form_fields,
+                    0,
+                );
+            }
+            ApplicationState::GoalsProgress => {
+                let popup_area = center_the_popup_area(
+                    f.area(),
+                    Constraint::Percentage(60),
+                    Constraint::Percentage(40),
+                );
+                let popup_block = Block::default()
+                    .borders(Borders::ALL)
+                    .border_style(Style::default().fg(Color::LightBlue))
+                    .title("Goals Progress");
+                let goal_areas =
+                    Layout::vertical([Constraint::Fill(1); 3]).split(popup_block.inner(popup_area));
+                f.render_widget(Clear, popup_area);
+                f.render_widget(popup_block, popup_area);
+                for ((period_name, goal_km), (current_km, goal_area)) in [
+                    ("Week", DEFAULT_WEEKLY_GOAL_KM),
+                    ("Month", self.monthly_goal_km()),
+                    ("Year", DEFAULT_YEARLY_GOAL_KM),
+                ]
+                .into_iter()
+                .zip(self.running_totals.into_iter().zip(goal_areas.iter()))
+                {
+                    let progress = if goal_km > 0.0 {
+                        current_km / goal_km
+                    } else {
+                        1.0
+                    };
+                    let goal_gauge = Gauge::default()
+                        .block(
+                            Block::default()
+                                .borders(Borders::ALL)
+                                .title(Span::styled(period_name, HEADER_STYLE)),
+                        )
+                        .gauge_style(Style::default().fg(goal_progress_color(progress)))
+                        .ratio(progress.clamp(0.0, 1.0))
+                        .label(Span::styled(
+                            format!(
+                                "{:.1}/{:.0} km ({:.0}%), {:.1} km to go",
+                                current_km,
+                                goal_km,
+                                progress * 100.0,
+                                (goal_km - current_km).max(0.0)
+                            ),
+                            Style::default()
+                                .fg(DEFAULT_TEXT_COLOR)
+                                .add_modifier(Modifier::BOLD),
+                        ));
+                    f.render_widget(goal_gauge, *goal_area);
                 }
             }
-        } else {
-            append_to_log("Todo list items don't exist").unwrap();
+            ApplicationState::Statistics => {
+                let top_week_rows: Vec<Row> = self
+                    .running_statistics
+                    .top_weeks
+                    .iter()
+                    .enumerate()
+                    .map(|(rank, (week_number, year, total_km))| {
+                        Row::new(vec![
+                            format!("{}.", rank + 1),
+                            format!("W{:02} {}", week_number, year),
+                            format!("{:.1} km", total_km),
+                        ])
+                        .style(row_style)
+                    })
+                    .collect();
+                let (longest_streak_days, longest_streak_end) =
+                    self.running_statistics.longest_streak;
+                let statistics_table = Table::new(
+                    top_week_rows,
+                    [
+                        Constraint::Length(4),
+                        Constraint::Fill(1),
+                        Constraint::Fill(1),
+                    ],
+                )
+                .header(Row::new(vec!["#", "Week", "Distance"]).style(HEADER_STYLE))
+                .block(
+                    render_block_with_title_and_subtitle(
+                        &format!("Top {} Weeks", TOP_WEEKS_COUNT),
+                        &format!(
+                            "Best streak: {} days (ended {}), Months at goal: {}/{}",
+                            longest_streak_days,
+                            longest_streak_end.format(RUN_ENTRY_DATE_FORMAT),
+                            self.running_statistics.months_above_goal,
+                            chrono::Local::now().month()
+                        ),
+                    )
+                    .title_bottom(
+                        Line::from(self.week_calories().map_or(String::new(), |week_calories| {
+                            format!("Week: ~{:.0} kcal", week_calories)
+                        }))
+                        .right_aligned(),
+                    )
+                    .border_style(Style::default().fg(Color::LightBlue)),
+                );
+                let workout_type_lines = self.workout_type_lines();
+                // borders + one row per workout type
+                let workout_type_height = workout_type_lines.len() as u16 + 2;
+                let popup_area = center_the_popup_area(
+                    f.area(),
+                    Constraint::Length(80),
+                    Constraint::Length(
+                        TOP_WEEKS_COUNT as u16 + 3 + RUN_DAY_WEATHER_HEIGHT + workout_type_height,
+                    ),
+                );
+                let [table_area, run_day_weather_area, workout_type_area] = Layout::vertical([
+                    // borders + header + one row per week
+                    Constraint::Length(TOP_WEEKS_COUNT as u16 + 3),
+                    Constraint::Length(RUN_DAY_WEATHER_HEIGHT),
+                    Constraint::Length(workout_type_height),
+                ])
+                .areas(popup_area);
+                f.render_widget(Clear, popup_area);
+                f.render_widget(statistics_table, table_area);
+                f.render_widget(
+                    Paragraph::new(self.run_day_weather_lines()).block(
+                        Block::default()
+                            .borders(Borders::ALL)
+                            .border_style(Style::default().fg(Color::LightBlue))
+                            .title(Span::styled("Weather (forecast days)", HEADER_STYLE)),
+                    ),
+                    run_day_weather_area,
+                );
+                f.render_widget(
+                    Paragraph::new(workout_type_lines).block(
+                        Block::default()
+                            .borders(Borders::ALL)
+                            .border_style(Style::default().fg(Color::LightBlue))
+                            .title(Span::styled("Workout Types", HEADER_STYLE)),
+                    ),
+                    workout_type_area,
+                );
+            }
+            ApplicationState::HourlyForecast => {
+                let hourly_rows: Vec<Row> = match &self.hourly_forecast {
+                    Some(hourly_forecast) => hourly_forecast
+                        .upcoming_rows(chrono::Local::now().naive_local(), HOURLY_FORECAST_HOURS)
+                        .into_iter()
+                        .map(|hourly_row| Row::new(hourly_row).style(row_style))
+                        .collect(),
+                    None => vec![Row::new(vec!["no data", "-", "-"]).style(row_style)],
+                };
+                let hourly_table = Table::new(
+                    hourly_rows,
+                    [
+                        Constraint::Length(10),
+                        Constraint::Fill(1),
+                        Constraint::Fill(1),
+                    ],
+                )
+                .header(Row::new(vec!["Hour", "Temp", "Rain"]).style(HEADER_STYLE))
+                .row_highlight_style(Style::default().add_modifier(Modifier::REVERSED))
+                .block(
+                    Block::default()
+                        .borders(Borders::ALL)
+                        .border_style(Style::default().fg(Color::LightBlue))
+                        .title("Hourly Forecast"),
+                );
+                render_centered_stateful_popup(
+                    f,
+                    f.area(),
+                    Constraint::Percentage(40),
+                    Constraint::Percentage(60),
+                    hourly_table,
+                    &mut self.hourly_forecast_table_state,
+                );
+            }
+            _ => (),
         }
         /* #endregion */
+        render_debug_overlay(self, f);
+        #[cfg(debug_assertions)]
+        {
+            self.last_frame_duration = frame_started_at.elapsed();
+        }
+    }
 
-        /* #region running schedule */
-        let mut am_running_items: Vec<&str> = vec!["rest"; 7];
-        let mut pm_running_items: Vec<&str> = vec!["rest"; 7];
-        let mut debug_vector: Vec<&str> = vec![];
-        let mut date_to_index_map: HashMap<String, u16> = HashMap::new();
-        let current_date = chrono::Local::now().naive_local().date();
+    fn get_environment_dict(&self) -> core::result::Result<Config, AppError> {
+        Config::load(&self.environment_path)
+    }
 
-        // Loop through 7 days
-        for day_increment in 0..7 {
-            // Calculate the new date by adding `day_increment` days
-            let new_date = current_date + chrono::Duration::days(day_increment as i64);
+    /// Sets the status bar notice and returns true if writes are disabled
+    fn refuse_write_in_read_only_mode(&mut self) -> bool {
+        if self.read_only {
+            self.status_message = "Read-only mode: no changes saved".to_string();
+        }
+        self.read_only
+    }
+
+    /// Calorie estimate of the current week up to today, `None` while `weight_kg` is unset
+    fn week_calories(&self) -> Option<f64> {
+        let weight_kg = self.environment_dict.settings.weight_kg?;
+        let [week_start, ..] = current_week_dates(
+            self.environment_dict
+                .settings
+                .week_start
+                .unwrap_or_default(),
+        );
+        Some(calories_for_period(
+            &self.environment_dict.running_schedule,
+            week_start,
+            chrono::Local::now().date_naive(),
+            weight_kg,
+        ))
+    }
 
-            // Format the date as "month/day/year"
-            let date_string_in_loop = new_date.format("%m/%d/%Y").to_string();
+    fn monthly_goal_km(&self) -> f64 {
+        self.environment_dict
+            .running_goals
+            .monthly_km
+            .unwrap_or(DEFAULT_MONTHLY_GOAL_KM)
+    }
 
-            // Insert into the map
-            date_to_index_map.insert(date_string_in_loop, day_increment);
-        }
-        // append_to_log(&format!("{:?}", date_to_index_map)).unwrap();
-        if let Some(running_items) = self.environment_dict["running_schedule"].as_array() {
-            for todo_item in running_items.iter() {
-                if let Some(dict_date_key_str) = todo_item["date"].as_str() {
-                    if date_to_index_map.contains_key(dict_date_key_str) {
-                        if let Some(insertion_index) = date_to_index_map.get(dict_date_key_str) {
-                            if let Some(current_am_string) = todo_item["am"].as_str() {
-                                am_running_items[*insertion_index as usize] = current_am_string;
-                            }
-                            if let Some(current_pm_string) = todo_item["pm"].as_str() {
-                                pm_running_items[*insertion_index as usize] = current_pm_string;
-                            }
-                        }
-                    }
-                }
-            }
+    fn popup_title(&self, title: &str) -> String {
+        if self.read_only {
+            title.to_string() + " [read-only: no changes saved]"
         } else {
-            append_to_log("Running schedule items don't exist").unwrap();
+            title.to_string()
         }
-        /* #endregion */
+    }
 
-        /* #region table */
-        let today = chrono::Local::now();
-        let weekday_index = today.weekday().num_days_from_monday() as usize;
-        let mut weekdays_array = vec![""];
-        weekdays_array.extend(WEEKDAY_STRINGS.iter().cycle().skip(weekday_index).take(7));
-        let weekdays_array: [&str; 8] = weekdays_array.try_into().expect("Incorrect array size");
+    fn update_running_totals_in_json(&mut self) -> core::result::Result<(), AppError> {
+        if self.refuse_write_in_read_only_mode() {
+            return core::result::Result::Ok(());
+        }
+        self.environment_dict.running_totals = self.running_totals;
+        self.environment_dict.running_totals_last_updated =
+            Some(chrono::Local::now().naive_local());
+        self.write_environment_dict_to_json()
+    }
 
-        let header = weekdays_array
-            .into_iter()
-            .map(Cell::from)
-            .collect::<Row>()
-            .style(HEADER_STYLE)
-            .height(1);
+    /// Saves the schedule to the file it was read from and everything else to the json, which
+    /// keeps its own schedule while another `--format` is active
+    fn save_environment_dict(&self) -> core::result::Result<(), AppError> {
+        let running_schedule = &self.environment_dict.running_schedule;
+        match self.data_format {
+            DataFormat::Json => return self.environment_dict.save(&self.environment_path),
+            DataFormat::Toml => {
+                save_schedule_to_toml(Path::new(SCHEDULE_PATH_TOML), running_schedule)?
+            }
+            DataFormat::Org => {
+                save_schedule_to_org(Path::new(SCHEDULE_PATH_ORG), running_schedule)?
+            }
+        }
+        Config {
+            running_schedule: self.json_running_schedule.clone(),
+            ..self.environment_dict.clone()
+        }
+        .save(&self.environment_path)
+    }
 
-        let mut am_running_items_table = vec!["Training AM"];
-        am_running_items_table.append(&mut am_running_items);
-        let mut pm_running_items_table = vec!["Training PM"];
-        pm_running_items_table.append(&mut pm_running_items);
-        let mut weather_items_table = vec!["Weather", "Sunny"];
-        weather_items_table.append(&mut debug_vector);
-        let row_style = Style::default().fg(Color::Yellow);
-        let rows = [
-            Row::new(vec!["Dawn start", "7:12"]).style(row_style),
-            Row::new(vec!["Dawn end", "7:42"]).style(row_style),
-            Row::new(vec!["Dusk start", "20:12"]).style(row_style),
-            Row::new(vec!["Dusk end", "20:50"]).style(row_style),
-            Row::new(weather_items_table).style(row_style),
-            Row::new(vec!["Low", "-2°C"]).style(row_style),
-            Row::new(vec!["High", "7°C"]).style(row_style),
-            Row::new(am_running_items_table).style(row_style),
-            Row::new(pm_running_items_table).style(row_style),
-        ];
-        let widths = [
-            Constraint::Length(14),
-            Constraint::Fill(1),
-            Constraint::Fill(1),
-            Constraint::Fill(1),
-            Constraint::Fill(1),
-            Constraint::Fill(1),
-            Constraint::Fill(1),
-            Constraint::Fill(1),
-        ];
-        let table_bottom_left = Table::new(rows, widths)
-            .header(header)
-            .highlight_symbol(Text::from(vec![
-                "".into(),
-                VERTICAL_BAR_CHARACTER.into(),
-                VERTICAL_BAR_CHARACTER.into(),
-                "".into(),
-            ]))
-            .highlight_spacing(HighlightSpacing::Always);
-        /* #endregion */
+    /// Callers check [`App::refuse_write_in_read_only_mode`] before editing anything, so
+    /// read-only mode never shows changes that aren't saved
+    fn write_environment_dict_to_json(&mut self) -> core::result::Result<(), AppError> {
+        if self.refuse_write_in_read_only_mode() {
+            return core::result::Result::Ok(());
+        }
+        self.has_unsaved_changes = true;
+        // schedule edits go through here, so the totals never lag behind them
+        self.recompute_running_totals();
+        self.environment_dict.running_totals = self.running_totals;
+        self.sort_running_schedule_in_json();
+        self.save_environment_dict()?;
+        self.has_unsaved_changes = false;
+        self.last_save_time = Some(Instant::now());
+        core::result::Result::Ok(())
+    }
+}
 
-        /* #region widgets */
+/// `constraint` while both the panel and the one it shares a split with are visible,
+/// the whole split when the sibling is hidden and nothing when the panel itself is hidden
+fn panel_constraint(visible: bool, sibling_visible: bool, constraint: Constraint) -> Constraint {
+    match (visible, sibling_visible) {
+        (false, _) => Constraint::Length(0),
+        (true, false) => Constraint::Fill(1),
+        (true, true) => constraint,
+    }
+}
 
-        let top_right_border_set = symbols::border::Set {
-            top_left: symbols::line::NORMAL.horizontal_down,
-            ..symbols::border::PLAIN
-        };
-        let collapsed_top_and_left_border_set = symbols::border::Set {
-            top_left: symbols::line::NORMAL.vertical_right,
-            top_right: symbols::line::NORMAL.vertical_left,
-            bottom_left: symbols::line::NORMAL.horizontal_up,
-            ..symbols::border::PLAIN
+/// `text` with every case-insensitive occurrence of `term` in `highlight_style`
+fn highlight_matches(text: &str, term: &str, highlight_style: Style) -> Line<'static> {
+    let mut spans: Vec<Span> = vec![];
+    let mut unmatched_start = 0;
+    for (index, _) in text.char_indices() {
+        if index < unmatched_start {
+            continue;
+        }
+        let Some(match_length) = case_insensitive_match_length(&text[index..], term) else {
+            continue;
         };
-        let rounded_totals: Vec<_> = self.running_totals.iter().map(|num| num.round()).collect();
-        let [week_current, month_current, year_current]: [f64; 3] = rounded_totals
-            .try_into()
-            .expect("Expected exactly 3 elements in running_totals");
+        if unmatched_start < index {
+            spans.push(Span::raw(text[unmatched_start..index].to_string()));
+        }
+        spans.push(Span::styled(
+            text[index..index + match_length].to_string(),
+            highlight_style,
+        ));
+        unmatched_start = index + match_length;
+    }
+    if unmatched_start < text.len() {
+        spans.push(Span::raw(text[unmatched_start..].to_string()));
+    }
+    Line::from(spans)
+}
 
-        let week_max = f64::max(110.0, week_current);
-        let month_max = f64::max(400.0, month_current);
-        let year_max = f64::max(5000.0, year_current);
-        let label_style_gauge = Style::default()
-            .fg(DEFAULT_TEXT_COLOR)
-            .add_modifier(Modifier::DIM);
-        let gauge_week = Gauge::default()
-            .block(Block::new().borders(Borders::ALL))
-            .gauge_style(GAUGE4_COLOR)
-            .ratio(week_current / week_max)
-            .label(Span::styled(
-                week_current.to_string() + "/" + &week_max.to_string(),
-                label_style_gauge,
-            ));
-        let gauge_month = Gauge::default()
-            .gauge_style(GAUGE4_COLOR)
-            .block(Block::new().borders(Borders::ALL))
-            .ratio(month_current / month_max)
-            .label(Span::styled(
-                month_current.to_string() + "/" + &month_max.to_string(),
-                label_style_gauge,
-            ));
-        let gauge_year = Gauge::default()
-            .gauge_style(GAUGE4_COLOR)
-            .block(Block::new().borders(Borders::ALL))
-            .ratio(year_current / year_max)
-            .label(Span::styled(
-                year_current.to_string() + "/" + &year_max.to_string(),
-                label_style_gauge,
-            ));
-        /* #endregion */
+fn activity_cell(activity_type: &ActivityType) -> Cell<'static> {
+    Cell::from(activity_type.to_string())
+        .style(ActivityType::apply_style_to_activity(activity_type))
+}
 
-        /* #region rendering */
-        match self.application_state {
-            ApplicationState::InsertRunPopup | ApplicationState::AddToRunningTotals => {
-                let centered_area = App::center_the_popup_area(
-                    f.area(),
-                    Constraint::Percentage(20),
-                    Constraint::Length(3), // top and bottom border + content
-                );
-                self.textarea_widget.set_block(
-                    Block::default()
-                        .borders(Borders::ALL)
-                        .border_style(Style::default().fg(Color::LightBlue))
-                        .title("Running Input"),
-                );
-                self.textarea_widget
-                    .set_style(Style::default().fg(Color::Yellow));
-                self.textarea_widget.set_placeholder_style(Style::default());
-                self.textarea_widget.set_placeholder_text("prompt message");
-                f.render_widget(Clear, centered_area);
-                f.render_widget(&self.textarea_widget, centered_area);
-            }
-            _ => (),
+/// Byte length of the prefix of `text` matching `term` ignoring case, `None` if it doesn't match
+fn case_insensitive_match_length(text: &str, term: &str) -> Option<usize> {
+    if term.is_empty() {
+        return None;
+    }
+    let mut text_chars = text.char_indices();
+    for term_char in term.chars() {
+        let (_, text_char) = text_chars.next()?;
+        if !text_char.to_lowercase().eq(term_char.to_lowercase()) {
+            return None;
         }
-        f.render_widget(
-            self.shortcut_list_text_block.clone().block(
-                Block::new()
-                    .borders(Borders::TOP | Borders::RIGHT | Borders::LEFT)
-                    .border_set(top_right_border_set),
-            ),
-            layout_bottom_middle[0],
-        );
-        f.render_widget(
-            Paragraph::new("Todo plan for today with spans and calendar").block(
-                Block::new()
-                    .borders(Borders::LEFT | Borders::RIGHT | Borders::TOP | Borders::BOTTOM),
-            ),
-            layout_left_side[0],
-        );
-        f.render_widget(
-            Paragraph::new(todo_list_text).block(Block::new().borders(Borders::ALL)),
-            layout_right[0],
+    }
+    Some(text_chars.next().map_or(text.len(), |(index, _)| index))
+}
+
+/// Priority, done checkbox, text and due date columns, the sorted column marked in the header
+fn todo_table(items: &[TodoItem], sorted_by_priority: bool) -> Table<'static> {
+    let today = chrono::Local::now().date_naive();
+    let header = Row::new(vec![
+        if sorted_by_priority { "P▼" } else { "P" },
+        "",
+        "Task",
+        "Due",
+    ])
+    .style(HEADER_STYLE);
+    let rows: Vec<Row> = items
+        .iter()
+        .map(|todo_item| {
+            Row::new(vec![
+                todo_item.priority.to_string(),
+                if todo_item.done { "✓" } else { " " }.to_string(),
+                todo_item.text.clone(),
+                todo_item.due_date.map_or(String::new(), |due_date| {
+                    due_date.format("%Y-%m-%d").to_string()
+                }),
+            ])
+            .style(todo_item_style(todo_item, today))
+        })
+        .collect();
+    Table::new(rows, TODO_TABLE_WIDTHS).header(header)
+}
+
+/// Tags as `│tag│` chips with rounded cyan borders, full boxes when `area` is 3 rows tall,
+/// followed by `+N more` at the right edge for the ones that don't fit
+fn render_todo_item_tags(tags: &[String], area: Rect, frame: &mut Frame) {
+    let chip_widths: Vec<u16> = tags
+        .iter()
+        .map(|tag| tag.chars().count() as u16 + 2)
+        .collect();
+    let fits = |chip_count: usize, reserved_width: u16| {
+        let chips_width: u16 = chip_widths[..chip_count].iter().sum::<u16>()
+            + chip_count.saturating_sub(1) as u16 * TAG_CHIP_SPACING;
+        chips_width + reserved_width <= area.width
+    };
+    let mut shown_count = tags.len();
+    let mut overflow_text = String::new();
+    while !fits(shown_count, overflow_text.chars().count() as u16) && shown_count > 0 {
+        shown_count -= 1;
+        overflow_text = format!(" +{} more", tags.len() - shown_count);
+    }
+    let chip_borders = if area.height >= 3 {
+        Borders::ALL
+    } else {
+        Borders::LEFT | Borders::RIGHT
+    };
+    let chip_areas = Layout::horizontal(
+        chip_widths[..shown_count]
+            .iter()
+            .map(|chip_width| Constraint::Length(*chip_width)),
+    )
+    .flex(Flex::Start)
+    .spacing(TAG_CHIP_SPACING)
+    .split(area);
+    for (tag, chip_area) in tags.iter().zip(chip_areas.iter()) {
+        frame.render_widget(
+            Paragraph::new(tag.as_str())
+                .style(Style::default().fg(DEFAULT_TEXT_COLOR))
+                .block(
+                    Block::new()
+                        .borders(chip_borders)
+                        .border_type(BorderType::Rounded)
+                        .border_style(Style::default().fg(Color::Cyan)),
+                ),
+            *chip_area,
         );
-        f.render_widget(
-            table_bottom_left
-                .block(Block::new().borders(Borders::TOP | Borders::LEFT | Borders::BOTTOM)),
-            layout_left_bottom[0],
+    }
+    if !overflow_text.is_empty() {
+        frame.render_widget(
+            Line::styled(overflow_text, Style::default().fg(Color::Cyan)).right_aligned(),
+            area,
         );
-        f.render_widget(gauge_week, layout_gauges[0]);
-        f.render_widget(gauge_month, layout_gauges[1]);
-        f.render_widget(gauge_year, layout_gauges[2]);
-        f.render_widget(
-            Paragraph::new(datetime_text).block(
-                Block::new()
+    }
+}
+
+/// Red when overdue, bold yellow when due today, gray without a due date, dimmed once done
+fn todo_item_style(todo_item: &TodoItem, today: chrono::NaiveDate) -> Style {
+    let todo_item_style = match todo_item.due_date {
+        Some(due_date) if due_date < today => Style::default().fg(Color::Red),
+        Some(due_date) if due_date == today => Style::default()
+            .fg(Color::Yellow)
+            .add_modifier(Modifier::BOLD),
+        Some(_) => Style::default().fg(DEFAULT_TEXT_COLOR),
+        None => Style::default().fg(Color::DarkGray),
+    };
+    if todo_item.done {
+        todo_item_style.add_modifier(Modifier::DIM)
+    } else {
+        todo_item_style
+    }
+}
+
+/// Application state, running totals, todo count, last event and the previous frame's render
+/// time in the top right corner, toggled by the debug overlay keybinding
+#[cfg(debug_assertions)]
+fn render_debug_overlay(app: &App, frame: &mut Frame) {
+    if !app.debug_overlay_visible {
+        return;
+    }
+    let [overlay_area] = Layout::horizontal([Constraint::Length(DEBUG_OVERLAY_WIDTH)])
+        .flex(Flex::End)
+        .areas(Rect {
+            height: frame.area().height.min(DEBUG_OVERLAY_HEIGHT),
+            ..frame.area()
+        });
+    let debug_lines = vec![
+        Line::from(format!(
+            "state  {}",
+            describe_application_state(&app.application_state)
+        )),
+        Line::from(format!(
+            "totals {:.1} / {:.1} / {:.1} km",
+            app.running_totals[0], app.running_totals[1], app.running_totals[2]
+        )),
+        Line::from(format!("todos  {}", app.environment_dict.todo_list.len())),
+        Line::from(format!("event  {}", app.last_event_type)),
+        Line::from(format!("frame  {:?}", app.last_frame_duration)),
+    ];
+    frame.render_widget(Clear, overlay_area);
+    frame.render_widget(
+        Paragraph::new(debug_lines)
+            .style(Style::default().fg(Color::Gray).add_modifier(Modifier::DIM))
+            .block(
+                Block::default()
                     .borders(Borders::ALL)
-                    .border_set(collapsed_top_and_left_border_set),
+                    .border_style(Style::default().fg(Color::DarkGray))
+                    .title("debug"),
             ),
-            layout_bottom_middle[1],
-        );
-        /* #endregion */
-    }
+        overlay_area,
+    );
+}
 
-    fn get_environment_dict() -> serde_json::Value {
-        let file = match fs::File::open(hello_user::ENVIRONMENT_PATH_JSON) {
-            core::result::Result::Ok(res) => res,
-            Err(e) => {
-                println!("{}", e.to_string());
-                return Default::default();
-            }
-        };
-        let reader = BufReader::new(file);
-        let trainings_dict: serde_json::Value = match serde_json::from_reader(reader) {
-            core::result::Result::Ok(res) => res,
-            Err(e) => {
-                println!("{}", e.to_string());
-                return Default::default();
-            }
-        };
-        return trainings_dict;
+#[cfg(not(debug_assertions))]
+fn render_debug_overlay(_app: &App, _frame: &mut Frame) {}
+
+/// Red below half of a goal, yellow until 90% and green from there
+fn goal_progress_color(progress: f64) -> Color {
+    if progress < 0.5 {
+        Color::Red
+    } else if progress < 0.9 {
+        Color::Yellow
+    } else {
+        Color::Green
     }
+}
 
-    fn center_the_popup_area(area: Rect, horizontal: Constraint, vertical: Constraint) -> Rect {
-        let [area] = Layout::horizontal([horizontal])
-            .flex(Flex::Center)
-            .areas(area);
-        let [area] = Layout::vertical([vertical]).flex(Flex::Center).areas(area);
-        area
+/// Single red row in place of the weather rows of the schedule table
+fn display_weather_error_panel(error: &str, area: Rect, frame: &mut Frame) {
+    frame.render_widget(
+        Paragraph::new(format!("⚠ Weather unavailable: {}", error))
+            .style(Style::default().fg(Color::Red)),
+        area,
+    );
+}
+
+fn new_tokio_runtime() -> Option<tokio::runtime::Runtime> {
+    match tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()
+    {
+        core::result::Result::Ok(res) => Some(res),
+        Err(e) => {
+            log_message!(
+                LogLevel::Error,
+                &format!("tokio runtime creation failed: {}", e)
+            );
+            None
+        }
     }
+}
 
-    fn update_running_totals_in_json(&mut self) -> std::io::Result<()> {
-        self.environment_dict["running_totals"] = self.running_totals.into();
-        let updated_json = serde_json::to_string_pretty(&self.environment_dict)?;
-        let mut file = OpenOptions::new()
-            .write(true)
-            .truncate(true)
-            .open(ENVIRONMENT_PATH_JSON)?;
-        file.write_all(updated_json.as_bytes())?;
+/// Profile names end up in a file name, so only letters, digits, `-` and `_` are allowed
+fn profile_name_error(profile_name: &str) -> Option<String> {
+    let profile_name = profile_name.trim();
+    if profile_name.is_empty() {
+        Some("profile name is empty".to_string())
+    } else if !profile_name
+        .chars()
+        .all(|item| item.is_ascii_alphanumeric() || item == '-' || item == '_')
+    {
+        Some("profile names may only use letters, digits, - and _".to_string())
+    } else {
+        None
+    }
+}
 
-        core::result::Result::Ok(())
+/// Short human-readable label of a state, no `_` arm so new variants have to be named here
+fn describe_application_state(state: &ApplicationState) -> &'static str {
+    match state {
+        ApplicationState::Main => "Main",
+        ApplicationState::InsertRunPopup => "Insert Run",
+        ApplicationState::InsertCalendarItemPopup => "Insert Calendar Item",
+        ApplicationState::InsertTodoItemPopup => "Insert Todo",
+        ApplicationState::AddToRunningTotals => "Add Distance",
+        ApplicationState::HourlyForecast => "Hourly Forecast",
+        ApplicationState::ApiKeyPrompt => "API Key",
+        ApplicationState::ConfirmClearLog => "Confirm Clear Log",
+        ApplicationState::CommandMode => "Command",
+        ApplicationState::SetCountdown => "Set Countdown",
+        ApplicationState::Countdown => "Countdown",
+        ApplicationState::Stopwatch => "Stopwatch",
+        ApplicationState::TodoListPopup => "Todo List",
+        ApplicationState::ScheduleFilterPrompt => "Filter Schedule",
+        ApplicationState::FilteredScheduleView(_) => "Filtered Schedule",
+        ApplicationState::ValidationWarnings(_) => "Data Warnings",
+        ApplicationState::DuplicateEntryWarning(_) => "Duplicate Dates",
+        ApplicationState::TagFilter => "Tag Filter",
+        ApplicationState::NewProfile => "New Profile",
+        ApplicationState::ConfirmDeleteEntry(_) => "Delete Entry",
+        ApplicationState::CopyEntryToDate(_) => "Copy Entry",
+        ApplicationState::ConfigureWeatherLocations => "Weather Locations",
+        ApplicationState::YearHeatmap => "Year Heatmap",
+        ApplicationState::Statistics => "Statistics",
+        ApplicationState::GoalsProgress => "Goals Progress",
     }
 }
 
-fn append_to_log(message: &str) -> std::io::Result<()> {
-    let mut file = OpenOptions::new()
+fn clear_log_file() -> core::result::Result<(), AppError> {
+    OpenOptions::new()
         .write(true)
-        .append(true)
-        .open(LOG_FILE_PATH)
-        .unwrap();
-    if let Err(e) = writeln!(file, "{}", message) {
-        eprintln!("Couldn't write to file: {}", e);
-    }
+        .truncate(true)
+        .open(LOG_FILE_PATH)?;
+    append_to_log(&format!(
+        "Log cleared at {}",
+        chrono::Local::now().format("%Y-%m-%d %H:%M:%S")
+    ))?;
     core::result::Result::Ok(())
 }
 
-macro_rules! log_message { ($message:expr) => { let _ =?: append_to_log($message); }; }
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn copy_entry_key_does_not_quit() {
+        let mut app = App::new();
+        app.running = true;
+        app.on_key_event(KeyEvent::new(KeyCode::Char('c'), KeyModifiers::ALT));
+        assert!(app.running);
+        app.on_key_event(KeyEvent::new(KeyCode::Char('c'), KeyModifiers::CONTROL));
+        assert!(!app.running);
+    }
+
+    #[test]
+    fn read_only_mode_leaves_data_untouched() {
+        let mut app = App::new().read_only(true);
+        app.running_totals = [12.0, 40.0, 300.0];
+        app.environment_dict.running_schedule = vec![RunEntry {
+            date: "05/07/2024".to_string(),
+            am: Some(ActivityType::Easy),
+            ..Default::default()
+        }];
+        app.handle_app_event(AppEvent::ResetWeeklyDistance);
+        assert_eq!(app.running_totals, [12.0, 40.0, 300.0]);
+        assert!(app.environment_dict.weekly_distance_reset.is_none());
+        assert_eq!(app.status_message, "Read-only mode: no changes saved");
+        app.application_state = ApplicationState::CopyEntryToDate("05/07/2024".to_string());
+        app.copy_schedule_entry(NaiveDate::from_ymd_opt(2024, 5, 14).unwrap());
+        assert_eq!(app.environment_dict.running_schedule.len(), 1);
+        assert!(!app.has_unsaved_changes);
+    }
+
+    #[test]
+    fn todo_selection_follows_the_displayed_rows() {
+        let mut app = App::new().read_only(true);
+        app.environment_dict.todo_list = [
+            ("stretch", 1, "home"),
+            ("race", 5, "run"),
+            ("shoes", 3, "run"),
+        ]
+        .into_iter()
+        .map(|(text, priority, todo_tag)| TodoItem {
+            text: text.to_string(),
+            priority,
+            tags: vec![todo_tag.to_string()],
+            ..Default::default()
+        })
+        .collect();
+        app.todo_sort_by_priority = true;
+        let mut selected_indices = vec![];
+        for _ in 0..4 {
+            app.select_todo_item(1);
+            selected_indices.push(app.selected_todo_index.unwrap());
+        }
+        assert_eq!(selected_indices, vec![1, 2, 0, 0]);
+        app.move_selected_todo_item(-1);
+        assert_eq!(app.environment_dict.todo_list[0].text, "stretch");
+        assert_eq!(app.selected_todo_index, Some(0));
+        // the selected item is hidden by the filter, so the first shown row is selected
+        app.todo_sort_by_priority = false;
+        app.todo_tags_filter = vec!["run".to_string()];
+        app.select_todo_item(1);
+        assert_eq!(app.selected_todo_index, Some(1));
+        app.select_todo_item(1);
+        assert_eq!(app.selected_todo_index, Some(2));
+    }
+}
@@ -0,0 +1,59 @@
+use crossterm::Command;
+use std::fmt;
+
+const BASE64_ALPHABET: &[u8; 64] =
+    b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+/// Standard padded base64 of `bytes`
+fn base64_encode(bytes: &[u8]) -> String {
+    let mut encoded = String::with_capacity(bytes.len().div_ceil(3) * 4);
+    for chunk in bytes.chunks(3) {
+        let group = chunk.iter().enumerate().fold(0u32, |group, (index, byte)| {
+            group | (*byte as u32) << (16 - 8 * index)
+        });
+        for index in 0..4 {
+            if index <= chunk.len() {
+                encoded.push(BASE64_ALPHABET[(group >> (18 - 6 * index) & 0x3F) as usize] as char);
+            } else {
+                encoded.push('=');
+            }
+        }
+    }
+    encoded
+}
+
+/// Puts the text on the system clipboard through the terminal (OSC 52), terminals without
+/// support ignore it
+pub struct CopyToClipboard<'a>(pub &'a str);
+
+impl Command for CopyToClipboard<'_> {
+    fn write_ansi(&self, f: &mut impl fmt::Write) -> fmt::Result {
+        write!(f, "\x1B]52;c;{}\x07", base64_encode(self.0.as_bytes()))
+    }
+
+    #[cfg(windows)]
+    fn execute_winapi(&self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn base64_encode_matches_known_encodings() {
+        assert_eq!(base64_encode(b""), "");
+        assert_eq!(base64_encode(b"Man"), "TWFu");
+        assert_eq!(base64_encode(b"Ma"), "TWE=");
+        assert_eq!(base64_encode(b"M"), "TQ==");
+        assert_eq!(base64_encode("Ø 10 km".as_bytes()), "w5ggMTAga20=");
+    }
+
+    #[test]
+    fn copy_to_clipboard_writes_an_osc_52_sequence() {
+        let mut ansi = String::new();
+        CopyToClipboard("Man").write_ansi(&mut ansi).unwrap();
+        assert_eq!(ansi, "\x1B]52;c;TWFu\x07");
+    }
+}
@@ -0,0 +1,275 @@
+use crate::error::AppError;
+use crate::panel_functionalities::calendar_todo::TodoItem;
+use crate::panel_functionalities::running_schedule::{ConflictStrategy, RunEntry};
+use crate::panel_functionalities::season::Hemisphere;
+use crate::panel_functionalities::statistics::FatigueThresholds;
+use crate::panel_functionalities::weather::WeatherLocation;
+use crate::util::WeekStart;
+use chrono::{NaiveDate, NaiveDateTime};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs::{self, OpenOptions};
+use std::io::{self, BufReader, Write};
+use std::path::Path;
+
+const MAX_TODO_PRIORITY: u8 = 5;
+
+/// Typed contents of `ENVIRONMENT_PATH_JSON`
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Config {
+    /// Week, month and year totals in km
+    pub running_totals: [f64; 3],
+    /// Last change of `running_totals`, local time
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub running_totals_last_updated: Option<NaiveDateTime>,
+    /// Week distance cleared with ctrl+o, kept since the week total is recomputed from the
+    /// schedule on every write
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub weekly_distance_reset: Option<WeeklyDistanceReset>,
+    pub running_schedule: Vec<RunEntry>,
+    pub todo_list: Vec<TodoItem>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub running_history: Option<RunningHistory>,
+    /// City name to `(latitude, longitude)`
+    pub geocode_cache: BTreeMap<String, (f64, f64)>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub hourly_forecast_city: Option<String>,
+    /// Cities the weather is fetched for, the first one feeds the hourly forecast
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub weather_locations: Vec<WeatherLocation>,
+    pub settings: Settings,
+    pub running_goals: RunningGoals,
+    pub ui: UiSettings,
+    /// Key combination to `AppEvent` name, e.g. `"ctrl+r": "InsertRun"`, overriding the defaults
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub keybindings: BTreeMap<String, String>,
+    /// Keys this struct doesn't know about, kept so a write doesn't drop them
+    #[serde(flatten)]
+    pub extra: serde_json::Map<String, serde_json::Value>,
+}
+
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Settings {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub schedule_retention_days: Option<u32>,
+    /// `"km"` or `"mi"`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub distance_unit: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub week_start: Option<WeekStart>,
+    /// Days without a running totals update before the gauges are marked stale
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub staleness_warn_days: Option<u32>,
+    /// Which entry an import keeps for a date that is already in the schedule
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub import_conflict_strategy: Option<ConflictStrategy>,
+    /// Body weight for the calorie estimates, which are hidden while unset
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub weight_kg: Option<f64>,
+    /// Flips the season shown in the datetime panel, northern if unset
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub hemisphere: Option<Hemisphere>,
+    /// Load ratio and streak length of the rest recommendation in the shortcut panel
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub fatigue_thresholds: Option<FatigueThresholds>,
+}
+
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct UiSettings {
+    /// Marks the selected schedule row, 1 to 5 characters
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub highlight_symbol: Option<String>,
+    /// Schedule table header labels, monday first, the Japanese weekday names if unset
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub weekday_labels: Option<[String; 7]>,
+    /// Draws the top borders of the main panels as a color gradient
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub gradient_theme: Option<bool>,
+}
+
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RunningGoals {
+    /// Distances in km marked on the running total gauges
+    pub milestones: Vec<f64>,
+    /// Distance in km to run each month, also the scale of the month gauge
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub monthly_km: Option<f64>,
+}
+
+/// Distance subtracted from the recomputed week total while `week_start` is the current week
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct WeeklyDistanceReset {
+    pub week_start: NaiveDate,
+    pub offset_km: f64,
+}
+
+/// Distance totals keyed by `"YYYY-MM"` and `"YYYY"`
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RunningHistory {
+    pub monthly: BTreeMap<String, f64>,
+    pub yearly: BTreeMap<String, f64>,
+}
+
+impl Config {
+    pub fn load(path: &Path) -> Result<Self, AppError> {
+        let file = fs::File::open(path).map_err(|e| {
+            AppError::Io(io::Error::new(
+                e.kind(),
+                format!("opening {} failed: {}", path.display(), e),
+            ))
+        })?;
+        let reader = BufReader::new(file);
+        Ok(serde_json::from_reader(reader)?)
+    }
+
+    /// Overwrites `path`, creating it if missing, nothing is written if the json wouldn't load.
+    /// The json goes to a temporary file renamed over `path`, so a failed write keeps the old file
+    pub fn save(&self, path: &Path) -> Result<(), AppError> {
+        let updated_json = serialize_and_validate_json(self)?;
+        let temporary_path = path.with_extension("json.tmp");
+        let mut file = OpenOptions::new()
+            .write(true)
+            .create(true)
+            .truncate(true)
+            .open(&temporary_path)?;
+        file.write_all(updated_json.as_bytes())?;
+        file.sync_all()?;
+        fs::rename(&temporary_path, path)?;
+        Ok(())
+    }
+}
+
+/// Pretty json of `config`, checked to deserialize back so a saved file always loads again
+pub fn serialize_and_validate_json(config: &Config) -> Result<String, AppError> {
+    let json_str = serde_json::to_string_pretty(config)?;
+    serde_json::from_str::<Config>(&json_str)?;
+    Ok(json_str)
+}
+
+/// Every data problem found in `config`, empty when it looks sound
+pub fn validate_environment_dict(value: &Config) -> Vec<String> {
+    let mut violations = Vec::new();
+    for (running_total, period) in value.running_totals.iter().zip(["week", "month", "year"]) {
+        if *running_total < 0.0 {
+            violations.push(format!(
+                "{} running total is negative: {}",
+                period, running_total
+            ));
+        }
+    }
+    for run_entry in &value.running_schedule {
+        if run_entry.parsed_date().is_none() {
+            violations.push(format!(
+                "running schedule date {:?} can't be parsed",
+                run_entry.date
+            ));
+        }
+        if let Some(distance_km) = run_entry
+            .distance_km
+            .filter(|distance_km| *distance_km < 0.0)
+        {
+            violations.push(format!(
+                "running schedule distance on {} is negative: {}",
+                run_entry.date, distance_km
+            ));
+        }
+    }
+    // 0 is the default of items that never got a priority
+    for todo_item in &value.todo_list {
+        if todo_item.priority > MAX_TODO_PRIORITY {
+            violations.push(format!(
+                "todo item {:?} has priority {}, expected 1 to {}",
+                todo_item.text, todo_item.priority, MAX_TODO_PRIORITY
+            ));
+        }
+    }
+    violations
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn run_entry(date: &str, distance_km: Option<f64>) -> RunEntry {
+        RunEntry {
+            date: date.to_string(),
+            distance_km,
+            ..RunEntry::default()
+        }
+    }
+
+    fn todo_item(text: &str, priority: u8) -> TodoItem {
+        TodoItem {
+            text: text.to_string(),
+            priority,
+            ..TodoItem::default()
+        }
+    }
+
+    #[test]
+    fn sound_config_has_no_violations() {
+        let config = Config {
+            running_totals: [0.0, 12.5, 80.0],
+            running_schedule: vec![run_entry("03/14/2026", Some(10.0))],
+            todo_list: vec![
+                todo_item("stretch", 0),
+                todo_item("shoes", MAX_TODO_PRIORITY),
+            ],
+            ..Config::default()
+        };
+        assert!(validate_environment_dict(&config).is_empty());
+    }
+
+    #[test]
+    fn negative_running_total_is_reported_per_period() {
+        let config = Config {
+            running_totals: [5.0, -1.5, 0.0],
+            ..Config::default()
+        };
+        assert_eq!(
+            validate_environment_dict(&config),
+            vec!["month running total is negative: -1.5".to_string()]
+        );
+    }
+
+    #[test]
+    fn unparsable_schedule_date_is_reported() {
+        let config = Config {
+            running_schedule: vec![run_entry("2026-03-14", None)],
+            ..Config::default()
+        };
+        assert_eq!(
+            validate_environment_dict(&config),
+            vec!["running schedule date \"2026-03-14\" can't be parsed".to_string()]
+        );
+    }
+
+    #[test]
+    fn negative_schedule_distance_is_reported() {
+        let config = Config {
+            running_schedule: vec![run_entry("03/14/2026", Some(-4.0))],
+            ..Config::default()
+        };
+        assert_eq!(
+            validate_environment_dict(&config),
+            vec!["running schedule distance on 03/14/2026 is negative: -4".to_string()]
+        );
+    }
+
+    #[test]
+    fn todo_priority_above_maximum_is_reported() {
+        let config = Config {
+            todo_list: vec![todo_item("race", MAX_TODO_PRIORITY + 1)],
+            ..Config::default()
+        };
+        assert_eq!(
+            validate_environment_dict(&config),
+            vec!["todo item \"race\" has priority 6, expected 1 to 5".to_string()]
+        );
+    }
+}
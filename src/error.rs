@@ -0,0 +1,80 @@
+use std::error::Error;
+use std::fmt;
+use std::io;
+
+/// Error type of the library functions, turns into a `color_eyre::Report` with `?`
+#[derive(Debug)]
+pub enum AppError {
+    Io(io::Error),
+    Json(serde_json::Error),
+    Toml(toml::de::Error),
+    TomlSerialize(toml::ser::Error),
+    WeatherApi(String),
+    /// Every problem found, not just the first one
+    Validation(Vec<String>),
+}
+
+impl fmt::Display for AppError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AppError::Io(e) => write!(f, "io error: {}", e),
+            AppError::Json(e) => write!(f, "json error: {}", e),
+            AppError::Toml(e) => write!(f, "toml error: {}", e),
+            AppError::TomlSerialize(e) => write!(f, "toml error: {}", e),
+            AppError::WeatherApi(message) => write!(f, "weather api error: {}", message),
+            AppError::Validation(problems) => {
+                write!(f, "validation failed: {}", problems.join(", "))
+            }
+        }
+    }
+}
+
+impl Error for AppError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            AppError::Io(e) => Some(e),
+            AppError::Json(e) => Some(e),
+            AppError::Toml(e) => Some(e),
+            AppError::TomlSerialize(e) => Some(e),
+            AppError::WeatherApi(_) | AppError::Validation(_) => None,
+        }
+    }
+}
+
+impl From<io::Error> for AppError {
+    fn from(e: io::Error) -> Self {
+        AppError::Io(e)
+    }
+}
+
+impl From<serde_json::Error> for AppError {
+    fn from(e: serde_json::Error) -> Self {
+        AppError::Json(e)
+    }
+}
+
+impl From<toml::de::Error> for AppError {
+    fn from(e: toml::de::Error) -> Self {
+        AppError::Toml(e)
+    }
+}
+
+impl From<toml::ser::Error> for AppError {
+    fn from(e: toml::ser::Error) -> Self {
+        AppError::TomlSerialize(e)
+    }
+}
+
+/// The url is dropped since its query can carry the geocoding api key
+impl From<reqwest::Error> for AppError {
+    fn from(e: reqwest::Error) -> Self {
+        AppError::WeatherApi(e.without_url().to_string())
+    }
+}
+
+/// `open_meteo_api` reports all its failures as boxed errors
+impl From<Box<dyn Error>> for AppError {
+    fn from(e: Box<dyn Error>) -> Self {
+        AppError::WeatherApi(e.to_string())
+    }
+}
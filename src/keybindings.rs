@@ -0,0 +1,286 @@
+use crossterm::event::{KeyCode, KeyModifiers};
+use std::collections::{BTreeMap, HashMap};
+use std::str::FromStr;
+
+/// Action a key combination can be bound to in `environment_dict["keybindings"]`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AppEvent {
+    Quit,
+    CommandMode,
+    InsertCalendarItem,
+    InsertRun,
+    InsertTodo,
+    AddDistance,
+    ResetWeeklyDistance,
+    ToggleReadOnly,
+    ReconcileTotals,
+    ClearLog,
+    Countdown,
+    Stopwatch,
+    TagFilter,
+    ToggleWeatherPanel,
+    TodoListPopup,
+    NewProfile,
+    TogglePrioritySort,
+    HourlyForecast,
+    FocusNextPanel,
+    ToggleHighlightSpacing,
+    FilterSchedule,
+    DeleteScheduleEntry,
+    CopyScheduleEntry,
+    ConfigureWeatherLocations,
+    YearHeatmap,
+    Statistics,
+    GoalsProgress,
+    CopyWeeklyReport,
+    /// Does nothing in release builds
+    ToggleDebugOverlay,
+    /// Sent after the keybinding map was reloaded, not bindable itself
+    KeybindingsChanged,
+}
+
+impl AppEvent {
+    /// What the action does, as shown in the shortcut panel
+    pub fn description(&self) -> &'static str {
+        match self {
+            AppEvent::Quit => "quit",
+            AppEvent::CommandMode => "command mode",
+            AppEvent::InsertCalendarItem => "modify todo list",
+            AppEvent::InsertRun => "edit running schedule",
+            AppEvent::InsertTodo => "add to todo list",
+            AppEvent::AddDistance => "add distance to weekly total",
+            AppEvent::ResetWeeklyDistance => "reset weekly distance to 0",
+            AppEvent::ToggleReadOnly => "toggle read-only",
+            AppEvent::ReconcileTotals => "reconcile totals",
+            AppEvent::ClearLog => "clear log",
+            AppEvent::Countdown => "countdown in minutes to a time",
+            AppEvent::Stopwatch => "stopwatch",
+            AppEvent::TagFilter => "filter todo tags",
+            AppEvent::ToggleWeatherPanel => "weather forecast panel",
+            AppEvent::TodoListPopup => "todo popup",
+            AppEvent::NewProfile => "new profile",
+            AppEvent::TogglePrioritySort => "sort todos by priority",
+            AppEvent::HourlyForecast => "hourly forecast",
+            AppEvent::FocusNextPanel => "focus next panel",
+            AppEvent::ToggleHighlightSpacing => "toggle highlight spacing",
+            AppEvent::FilterSchedule => "filter schedule",
+            AppEvent::DeleteScheduleEntry => "delete schedule entry",
+            AppEvent::CopyScheduleEntry => "copy schedule entry",
+            AppEvent::ConfigureWeatherLocations => "weather locations",
+            AppEvent::YearHeatmap => "year heatmap",
+            AppEvent::Statistics => "all time stats",
+            AppEvent::GoalsProgress => "goals",
+            AppEvent::CopyWeeklyReport => "copy weekly report",
+            AppEvent::ToggleDebugOverlay => "debug overlay",
+            AppEvent::KeybindingsChanged => "reload shortcuts",
+        }
+    }
+}
+
+impl FromStr for AppEvent {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "Quit" => Ok(AppEvent::Quit),
+            "CommandMode" => Ok(AppEvent::CommandMode),
+            "InsertCalendarItem" => Ok(AppEvent::InsertCalendarItem),
+            "InsertRun" => Ok(AppEvent::InsertRun),
+            "InsertTodo" => Ok(AppEvent::InsertTodo),
+            "AddDistance" => Ok(AppEvent::AddDistance),
+            "ResetWeeklyDistance" => Ok(AppEvent::ResetWeeklyDistance),
+            "ToggleReadOnly" => Ok(AppEvent::ToggleReadOnly),
+            "ReconcileTotals" => Ok(AppEvent::ReconcileTotals),
+            "ClearLog" => Ok(AppEvent::ClearLog),
+            "Countdown" => Ok(AppEvent::Countdown),
+            "Stopwatch" => Ok(AppEvent::Stopwatch),
+            "TagFilter" => Ok(AppEvent::TagFilter),
+            "ToggleWeatherPanel" => Ok(AppEvent::ToggleWeatherPanel),
+            "TodoListPopup" => Ok(AppEvent::TodoListPopup),
+            "NewProfile" => Ok(AppEvent::NewProfile),
+            "TogglePrioritySort" => Ok(AppEvent::TogglePrioritySort),
+            "HourlyForecast" => Ok(AppEvent::HourlyForecast),
+            "FocusNextPanel" => Ok(AppEvent::FocusNextPanel),
+            "ToggleHighlightSpacing" => Ok(AppEvent::ToggleHighlightSpacing),
+            "FilterSchedule" => Ok(AppEvent::FilterSchedule),
+            "DeleteScheduleEntry" => Ok(AppEvent::DeleteScheduleEntry),
+            "CopyScheduleEntry" => Ok(AppEvent::CopyScheduleEntry),
+            "ConfigureWeatherLocations" => Ok(AppEvent::ConfigureWeatherLocations),
+            "YearHeatmap" => Ok(AppEvent::YearHeatmap),
+            "Statistics" => Ok(AppEvent::Statistics),
+            "GoalsProgress" => Ok(AppEvent::GoalsProgress),
+            "CopyWeeklyReport" => Ok(AppEvent::CopyWeeklyReport),
+            "ToggleDebugOverlay" => Ok(AppEvent::ToggleDebugOverlay),
+            _ => Err(format!("unknown action {}", s)),
+        }
+    }
+}
+
+/// Shifted letters arrive in either case depending on the terminal, so they are compared
+/// in lowercase
+pub fn normalize_key(modifiers: KeyModifiers, code: KeyCode) -> (KeyModifiers, KeyCode) {
+    match code {
+        KeyCode::Char(character) if modifiers.contains(KeyModifiers::SHIFT) => {
+            (modifiers, KeyCode::Char(character.to_ascii_lowercase()))
+        }
+        _ => (modifiers, code),
+    }
+}
+
+/// Text form of a key combination as [`parse_key_combination`] reads it, like `ctrl+shift+t`
+pub fn format_key_combination(modifiers: KeyModifiers, code: KeyCode) -> String {
+    let mut parts: Vec<String> = [
+        (KeyModifiers::CONTROL, "ctrl"),
+        (KeyModifiers::SHIFT, "shift"),
+        (KeyModifiers::ALT, "alt"),
+    ]
+    .into_iter()
+    .filter(|(modifier, _)| modifiers.contains(*modifier))
+    .map(|(_, modifier_name)| modifier_name.to_string())
+    .collect();
+    parts.push(match code {
+        KeyCode::Tab => "tab".to_string(),
+        KeyCode::Esc => "esc".to_string(),
+        KeyCode::Enter => "enter".to_string(),
+        KeyCode::Char(' ') => "space".to_string(),
+        KeyCode::Up => "up".to_string(),
+        KeyCode::Down => "down".to_string(),
+        KeyCode::Left => "left".to_string(),
+        KeyCode::Right => "right".to_string(),
+        KeyCode::Char(character) => character.to_ascii_lowercase().to_string(),
+        KeyCode::F(number) => format!("f{}", number),
+        _ => format!("{:?}", code).to_lowercase(),
+    });
+    parts.join("+")
+}
+
+/// Reads combinations like `ctrl+shift+t`, `alt+up` or `tab`, `None` for anything else
+pub fn parse_key_combination(text: &str) -> Option<(KeyModifiers, KeyCode)> {
+    let lowercase_text = text.trim().to_lowercase();
+    let (modifier_names, key_name) = match lowercase_text.rsplit_once('+') {
+        Some((modifier_names, key_name)) => (modifier_names, key_name),
+        None => ("", lowercase_text.as_str()),
+    };
+    let mut modifiers = KeyModifiers::NONE;
+    for modifier_name in modifier_names.split('+').filter(|item| !item.is_empty()) {
+        modifiers |= match modifier_name {
+            "ctrl" | "control" => KeyModifiers::CONTROL,
+            "shift" => KeyModifiers::SHIFT,
+            "alt" => KeyModifiers::ALT,
+            _ => return None,
+        };
+    }
+    let code = match key_name {
+        "tab" => KeyCode::Tab,
+        "esc" => KeyCode::Esc,
+        "enter" => KeyCode::Enter,
+        "space" => KeyCode::Char(' '),
+        "up" => KeyCode::Up,
+        "down" => KeyCode::Down,
+        "left" => KeyCode::Left,
+        "right" => KeyCode::Right,
+        _ if key_name.chars().count() == 1 => KeyCode::Char(key_name.chars().next()?),
+        _ => KeyCode::F(key_name.strip_prefix('f')?.parse().ok()?),
+    };
+    Some(normalize_key(modifiers, code))
+}
+
+/// Key combinations of `keybindings` mapped to their actions, plus one warning per entry
+/// that couldn't be read
+pub fn load_keybinding_map(
+    keybindings: &BTreeMap<String, String>,
+) -> (HashMap<(KeyModifiers, KeyCode), AppEvent>, Vec<String>) {
+    let mut keybinding_map = HashMap::new();
+    let mut warnings = Vec::new();
+    for (key_combination, action) in keybindings {
+        let Some(key) = parse_key_combination(key_combination) else {
+            warnings.push(format!("unrecognized key combination {}", key_combination));
+            continue;
+        };
+        match action.parse::<AppEvent>() {
+            Ok(app_event) => {
+                keybinding_map.insert(key, app_event);
+            }
+            Err(e) => warnings.push(format!("keybinding {}: {}", key_combination, e)),
+        }
+    }
+    (keybinding_map, warnings)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_key_combination_reads_modifiers_and_keys() {
+        assert_eq!(
+            parse_key_combination("ctrl+shift+T"),
+            Some((
+                KeyModifiers::CONTROL | KeyModifiers::SHIFT,
+                KeyCode::Char('t')
+            ))
+        );
+        assert_eq!(
+            parse_key_combination(" alt+up "),
+            Some((KeyModifiers::ALT, KeyCode::Up))
+        );
+        assert_eq!(
+            parse_key_combination("tab"),
+            Some((KeyModifiers::NONE, KeyCode::Tab))
+        );
+        assert_eq!(
+            parse_key_combination("control+f5"),
+            Some((KeyModifiers::CONTROL, KeyCode::F(5)))
+        );
+    }
+
+    #[test]
+    fn parse_key_combination_rejects_unknown_names() {
+        for text in ["", "hyper+a", "ctrl+pageup", "ctrl+fx"] {
+            assert_eq!(
+                parse_key_combination(text),
+                None,
+                "{text:?} should not parse"
+            );
+        }
+    }
+
+    #[test]
+    fn format_key_combination_round_trips_parse_key_combination() {
+        for text in [
+            "ctrl+r",
+            "ctrl+shift+d",
+            "alt+left",
+            "space",
+            "enter",
+            "f12",
+        ] {
+            let (modifiers, code) = parse_key_combination(text).unwrap();
+            assert_eq!(format_key_combination(modifiers, code), text);
+        }
+    }
+
+    #[test]
+    fn load_keybinding_map_warns_about_unreadable_entries() {
+        let keybindings = BTreeMap::from([
+            ("ctrl+r".to_string(), "AddDistance".to_string()),
+            ("ctrl+w".to_string(), "Fly".to_string()),
+            ("hyper+x".to_string(), "Quit".to_string()),
+        ]);
+        let (keybinding_map, warnings) = load_keybinding_map(&keybindings);
+        assert_eq!(
+            keybinding_map,
+            HashMap::from([(
+                (KeyModifiers::CONTROL, KeyCode::Char('r')),
+                AppEvent::AddDistance
+            )])
+        );
+        assert_eq!(
+            warnings,
+            vec![
+                "keybinding ctrl+w: unknown action Fly".to_string(),
+                "unrecognized key combination hyper+x".to_string()
+            ]
+        );
+    }
+}
@@ -1,5 +1,26 @@
+pub mod clipboard;
+pub mod config;
+pub mod error;
+pub mod keybindings;
+pub mod logging;
 pub mod panel_functionalities;
+pub mod util;
 
 
 pub const ENVIRONMENT_PATH_JSON: &str = "src/resources.json";
-pub const LOG_FILE_PATH: &str = "src/logs.txt";
\ No newline at end of file
+pub const SCHEDULE_PATH_TOML: &str = "src/schedule.toml";
+pub const SCHEDULE_PATH_ORG: &str = "src/schedule.org";
+pub const LOG_FILE_PATH: &str = "src/logs.txt";
+pub const CONFIG_DIR_NAME: &str = "startup_tui";
+pub const API_KEY_FILE_NAME: &str = "api_key";
+pub const API_KEY_ENV_VAR: &str = "STARTUP_TUI_API_KEY";
+
+/// `$XDG_CONFIG_HOME/startup_tui`, falling back to `~/.config/startup_tui`
+pub fn default_config_dir() -> Option<std::path::PathBuf> {
+    std::env::var_os("XDG_CONFIG_HOME")
+        .map(std::path::PathBuf::from)
+        .or_else(|| {
+            std::env::var_os("HOME").map(|home| std::path::Path::new(&home).join(".config"))
+        })
+        .map(|config_home| config_home.join(CONFIG_DIR_NAME))
+}
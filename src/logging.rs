@@ -0,0 +1,47 @@
+use crate::LOG_FILE_PATH;
+use std::fmt;
+use std::fs::OpenOptions;
+use std::io::Write;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LogLevel {
+    Info,
+    Warning,
+    Error,
+}
+
+impl fmt::Display for LogLevel {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LogLevel::Info => write!(f, "info"),
+            LogLevel::Warning => write!(f, "warning"),
+            LogLevel::Error => write!(f, "error"),
+        }
+    }
+}
+
+/// Appends `$message` to `LOG_FILE_PATH` and ignores write failures, a leading `LogLevel`
+/// prefixes the line with it, e.g. `log_message!(LogLevel::Error, "request failed")`
+#[macro_export]
+macro_rules! log_message {
+    ($level:expr, $message:expr) => {
+        let _ = $crate::logging::append_to_log_with_level($level, $message);
+    };
+    ($message:expr) => {
+        let _ = $crate::logging::append_to_log($message);
+    };
+}
+
+/// Appends `message` as one line to `LOG_FILE_PATH`, creating the file if it is missing
+pub fn append_to_log(message: &str) -> std::io::Result<()> {
+    let mut file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(LOG_FILE_PATH)?;
+    writeln!(file, "{}", message)
+}
+
+/// `[level] message`
+pub fn append_to_log_with_level(level: LogLevel, message: &str) -> std::io::Result<()> {
+    append_to_log(&format!("[{}] {}", level, message))
+}
@@ -0,0 +1,149 @@
+pub use app::App;
+
+pub mod app;
+
+use color_eyre::eyre::eyre;
+use crossterm::event::{DisableMouseCapture, PopKeyboardEnhancementFlags};
+use crossterm::terminal::{disable_raw_mode, LeaveAlternateScreen};
+use hello_user::config::Config;
+use hello_user::panel_functionalities::data_formats::DataFormat;
+use hello_user::panel_functionalities::running_schedule::{
+    merge_run_entries, parse_run_log_line, running_schedule_to_google_sheets_csv,
+    sort_running_schedule, ConflictStrategy,
+};
+use hello_user::{ENVIRONMENT_PATH_JSON, LOG_FILE_PATH};
+use std::io::{self, BufRead, Write};
+use std::path::Path;
+use std::time::Duration;
+
+const BENCHMARK_FRAME_COUNT: usize = 1000;
+
+fn main() -> color_eyre::Result<()> {
+    install_hooks()?;
+    if std::env::args().any(|argument| argument == "--import-log") {
+        return import_log();
+    }
+    if std::env::args().any(|argument| argument == "--benchmark-render") {
+        return benchmark_render();
+    }
+    if std::env::args().any(|argument| argument == "--export-sheets-csv") {
+        let config = Config::load(Path::new(ENVIRONMENT_PATH_JSON))?;
+        print!(
+            "{}",
+            running_schedule_to_google_sheets_csv(&config.running_schedule)
+        );
+        return Ok(());
+    }
+    let read_only = std::env::args().any(|argument| argument == "--read-only");
+    let data_format = match std::env::args()
+        .skip_while(|argument| argument != "--format")
+        .nth(1)
+    {
+        Some(format) => format.parse::<DataFormat>().map_err(|e| eyre!(e))?,
+        None => DataFormat::default(),
+    };
+    let terminal = ratatui::init();
+    let result = App::new()
+        .read_only(read_only)
+        .data_format(data_format)
+        .run(terminal);
+    ratatui::restore();
+    result
+}
+
+/// color_eyre's error and panic reports, with the terminal restored before a panic is printed
+/// so the message isn't lost in the alternate screen and the shell stays usable
+fn install_hooks() -> color_eyre::Result<()> {
+    let (panic_hook, eyre_hook) = color_eyre::config::HookBuilder::default().into_hooks();
+    eyre_hook.install()?;
+    std::panic::set_hook(Box::new(move |panic_info| {
+        restore_terminal();
+        eprintln!("{}", panic_hook.panic_report(panic_info));
+    }));
+    Ok(())
+}
+
+/// Best effort, the panic is reported either way
+fn restore_terminal() {
+    let _ = disable_raw_mode();
+    // the year heatmap captures the mouse while it is open, terminals without keyboard
+    // enhancement ignore the pop
+    let _ = crossterm::execute!(
+        io::stdout(),
+        PopKeyboardEnhancementFlags,
+        DisableMouseCapture,
+        LeaveAlternateScreen
+    );
+}
+
+/// Prints mean, median and p99 frame times of drawing the fixture config headlessly
+fn benchmark_render() -> color_eyre::Result<()> {
+    let mut frame_durations = App::new().benchmark_render(BENCHMARK_FRAME_COUNT)?;
+    frame_durations.sort();
+    let mean = frame_durations.iter().sum::<Duration>() / frame_durations.len() as u32;
+    let median = frame_durations[frame_durations.len() / 2];
+    let p99 = frame_durations[(frame_durations.len() * 99 / 100).min(frame_durations.len() - 1)];
+    println!(
+        "{} frames: mean {:?}, median {:?}, p99 {:?}",
+        frame_durations.len(),
+        mean,
+        median,
+        p99
+    );
+    Ok(())
+}
+
+/// Parses the free text log into run entries and merges them into the schedule after asking
+fn import_log() -> color_eyre::Result<()> {
+    let log_text = std::fs::read_to_string(LOG_FILE_PATH)?;
+    let imported_entries: Vec<_> = log_text.lines().filter_map(parse_run_log_line).collect();
+    if imported_entries.is_empty() {
+        println!("no run entries found in {}", LOG_FILE_PATH);
+        return Ok(());
+    }
+    for run_entry in &imported_entries {
+        println!(
+            "{}  AM {}  PM {}  {}",
+            run_entry.date,
+            run_entry
+                .am
+                .as_ref()
+                .map_or("-".to_string(), |item| item.to_string()),
+            run_entry
+                .pm
+                .as_ref()
+                .map_or("-".to_string(), |item| item.to_string()),
+            run_entry
+                .distance_km
+                .map_or("-".to_string(), |distance_km| format!("{} km", distance_km))
+        );
+    }
+    let environment_path = Path::new(ENVIRONMENT_PATH_JSON);
+    let mut config = Config::load(environment_path)?;
+    let conflict_strategy = config.settings.import_conflict_strategy.unwrap_or_default();
+    print!(
+        "merge {} entries into {}? dates already in the schedule {} [y/N] ",
+        imported_entries.len(),
+        ENVIRONMENT_PATH_JSON,
+        match conflict_strategy {
+            ConflictStrategy::PreferImported => "are replaced",
+            ConflictStrategy::PreferExisting => "are skipped",
+        }
+    );
+    io::stdout().flush()?;
+    let mut answer = String::new();
+    io::stdin().lock().read_line(&mut answer)?;
+    if !answer.trim().eq_ignore_ascii_case("y") {
+        println!("nothing merged");
+        return Ok(());
+    }
+    let added_entry_count = merge_run_entries(
+        &mut config.running_schedule,
+        imported_entries,
+        conflict_strategy,
+    );
+    sort_running_schedule(&mut config.running_schedule);
+    config.save(environment_path)?;
+    println!("merged {} new entries", added_entry_count);
+    Ok(())
+}
@@ -0,0 +1,34 @@
+use chrono::{Datelike, NaiveDate};
+
+/// Mean length of a lunar cycle in days
+const SYNODIC_MONTH_DAYS: f64 = 29.530588853;
+/// Julian date of the new moon of 2000-01-06 18:14 UTC
+const REFERENCE_NEW_MOON_JULIAN_DATE: f64 = 2451550.26;
+/// Julian date of midnight before 0001-01-01, day 0 of `num_days_from_ce`
+const COMMON_ERA_JULIAN_DATE_OFFSET: f64 = 1721424.5;
+const MOON_PHASE_ICONS: [&str; 8] = ["🌑", "🌒", "🌓", "🌔", "🌕", "🌖", "🌗", "🌘"];
+
+/// Julian date at noon UTC of `date`
+fn julian_date(date: NaiveDate) -> f64 {
+    date.num_days_from_ce() as f64 + COMMON_ERA_JULIAN_DATE_OFFSET + 0.5
+}
+
+/// Days since the last new moon at noon of `date`, 0 up to a synodic month
+fn moon_age_days(date: NaiveDate) -> f64 {
+    (julian_date(date) - REFERENCE_NEW_MOON_JULIAN_DATE).rem_euclid(SYNODIC_MONTH_DAYS)
+}
+
+/// One of the 8 moon phase characters, new moon first
+pub fn moon_phase_icon(date: NaiveDate) -> &'static str {
+    let phase_index = (moon_age_days(date) / SYNODIC_MONTH_DAYS * 8.0).round() as usize % 8;
+    MOON_PHASE_ICONS[phase_index]
+}
+
+/// Date of the next full moon from today on, today included, accurate to about a day
+/// since it uses the mean lunar cycle
+pub fn next_full_moon() -> NaiveDate {
+    let today = chrono::Local::now().date_naive();
+    let days_until_full_moon =
+        (SYNODIC_MONTH_DAYS / 2.0 - moon_age_days(today)).rem_euclid(SYNODIC_MONTH_DAYS);
+    today + chrono::Duration::days(days_until_full_moon.round() as i64)
+}
@@ -1,4 +1,93 @@
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeSet;
+
 fn write_calendar() {}
 
 pub fn read_calendar() {}
 
+/// One entry of `environment_dict["todo_list"]`, plain strings from older files are read as items
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(from = "TodoItemRepr")]
+pub struct TodoItem {
+    pub text: String,
+    /// Higher is more important
+    pub priority: u8,
+    /// `YYYY-MM-DD`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub due_date: Option<NaiveDate>,
+    pub done: bool,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum TodoItemRepr {
+    Text(String),
+    Item {
+        text: String,
+        #[serde(default)]
+        priority: u8,
+        #[serde(default)]
+        due_date: Option<NaiveDate>,
+        #[serde(default)]
+        done: bool,
+        #[serde(default)]
+        tags: Vec<String>,
+    },
+}
+
+impl From<TodoItemRepr> for TodoItem {
+    fn from(todo_item_repr: TodoItemRepr) -> Self {
+        match todo_item_repr {
+            TodoItemRepr::Text(text) => TodoItem {
+                text,
+                ..Default::default()
+            },
+            TodoItemRepr::Item {
+                text,
+                priority,
+                due_date,
+                done,
+                tags,
+            } => TodoItem {
+                text,
+                priority,
+                due_date,
+                done,
+                tags,
+            },
+        }
+    }
+}
+
+/// Moves the item at `from` to `to`, shifting the ones in between, indices past the end are clamped
+pub fn reorder_todo_items(items: &mut [TodoItem], from: usize, to: usize) {
+    if items.is_empty() || from >= items.len() {
+        return;
+    }
+    let to = to.min(items.len() - 1);
+    if from < to {
+        items[from..=to].rotate_left(1);
+    } else {
+        items[to..=from].rotate_right(1);
+    }
+}
+
+/// Indices of `items`, highest priority first, items of equal priority keep their list order
+pub fn todo_indices_by_priority(items: &[TodoItem]) -> Vec<usize> {
+    let mut sorted_indices: Vec<usize> = (0..items.len()).collect();
+    sorted_indices.sort_by_key(|index| std::cmp::Reverse(items[*index].priority));
+    sorted_indices
+}
+
+/// Every tag used by any item, sorted and deduplicated
+pub fn distinct_todo_tags(items: &[TodoItem]) -> Vec<String> {
+    items
+        .iter()
+        .flat_map(|todo_item| todo_item.tags.iter().cloned())
+        .collect::<BTreeSet<String>>()
+        .into_iter()
+        .collect()
+}
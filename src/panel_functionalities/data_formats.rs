@@ -0,0 +1,248 @@
+use crate::error::AppError;
+use crate::panel_functionalities::running_schedule::{RunEntry, RUN_ENTRY_DATE_FORMAT};
+use crate::util::{parse_distance_with_unit, validate_date_string};
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io;
+use std::path::Path;
+use std::str::FromStr;
+
+const ORG_TABLE_HEADER: &str = "| date | am | pm | distance |";
+const ORG_TABLE_SEPARATOR: &str = "|------+----+----+----------|";
+const ORG_DATE_FORMAT: &str = "<%Y-%m-%d %a>";
+
+/// Where the running schedule is read from, picked with `--format`
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub enum DataFormat {
+    /// `running_schedule` of `ENVIRONMENT_PATH_JSON`
+    #[default]
+    Json,
+    /// `[[schedule]]` tables of `SCHEDULE_PATH_TOML`, see `schema.toml`
+    Toml,
+    /// `| date | am | pm | distance |` table rows of `SCHEDULE_PATH_ORG`
+    Org,
+}
+
+impl FromStr for DataFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "json" => Ok(DataFormat::Json),
+            "toml" => Ok(DataFormat::Toml),
+            "org" => Ok(DataFormat::Org),
+            _ => Err(format!("unknown format {}, expected json, toml or org", s)),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+struct TomlSchedule {
+    #[serde(default)]
+    schedule: Vec<RunEntry>,
+}
+
+fn read_schedule_file(path: &Path) -> Result<String, AppError> {
+    fs::read_to_string(path).map_err(|e| {
+        AppError::Io(io::Error::new(
+            e.kind(),
+            format!("opening {} failed: {}", path.display(), e),
+        ))
+    })
+}
+
+/// Run entries of the `[[schedule]]` array in the toml file at `path`
+pub fn load_schedule_from_toml(path: &Path) -> Result<Vec<RunEntry>, AppError> {
+    let toml_text = read_schedule_file(path)?;
+    Ok(toml::from_str::<TomlSchedule>(&toml_text)?.schedule)
+}
+
+/// Overwrites the toml file at `path` with one `[[schedule]]` table per entry, comments in the
+/// file are not kept
+pub fn save_schedule_to_toml(path: &Path, schedule: &[RunEntry]) -> Result<(), AppError> {
+    let toml_text = toml::to_string(&TomlSchedule {
+        schedule: schedule.to_vec(),
+    })?;
+    fs::write(path, toml_text)?;
+    Ok(())
+}
+
+/// Run entries of the `| date | am | pm | distance |` table rows in the org file at `path`.
+/// Separator rows and rows without a readable date, like the header, are skipped, missing or
+/// empty cells are left unset
+pub fn load_schedule_from_org(path: &Path) -> Result<Vec<RunEntry>, AppError> {
+    let org_text = read_schedule_file(path)?;
+    Ok(org_text.lines().filter_map(parse_org_table_row).collect())
+}
+
+/// Replaces the table rows of the org file at `path` with `schedule`, the text before the first
+/// and after the last row is kept. A file without rows gets a new table appended. Durations
+/// have no column and aren't written
+pub fn save_schedule_to_org(path: &Path, schedule: &[RunEntry]) -> Result<(), AppError> {
+    let org_text = match fs::read_to_string(path) {
+        Ok(org_text) => org_text,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => String::new(),
+        Err(e) => return Err(e.into()),
+    };
+    let lines: Vec<&str> = org_text.lines().collect();
+    let row_indices: Vec<usize> = lines
+        .iter()
+        .enumerate()
+        .filter_map(|(index, line)| parse_org_table_row(line).map(|_| index))
+        .collect();
+    let rows = schedule.iter().map(format_org_table_row);
+    let updated_lines: Vec<String> = match (row_indices.first(), row_indices.last()) {
+        (Some(&first_row), Some(&last_row)) => lines[..first_row]
+            .iter()
+            .map(|line| line.to_string())
+            .chain(rows)
+            .chain(lines[last_row + 1..].iter().map(|line| line.to_string()))
+            .collect(),
+        _ => lines
+            .iter()
+            .map(|line| line.to_string())
+            .chain([
+                ORG_TABLE_HEADER.to_string(),
+                ORG_TABLE_SEPARATOR.to_string(),
+            ])
+            .chain(rows)
+            .collect(),
+    };
+    fs::write(path, updated_lines.join("\n") + "\n")?;
+    Ok(())
+}
+
+fn format_org_table_row(run_entry: &RunEntry) -> String {
+    let date = run_entry
+        .parsed_date()
+        .map_or(run_entry.date.clone(), |date| {
+            date.format(ORG_DATE_FORMAT).to_string()
+        });
+    let session = |session: &Option<_>| session.as_ref().map_or(String::new(), ToString::to_string);
+    format!(
+        "| {} | {} | {} | {} |",
+        date,
+        session(&run_entry.am),
+        session(&run_entry.pm),
+        run_entry
+            .distance_km
+            .map_or(String::new(), |distance_km| distance_km.to_string())
+    )
+}
+
+fn parse_org_table_row(line: &str) -> Option<RunEntry> {
+    let line = line.trim();
+    if !line.starts_with('|')
+        || line
+            .chars()
+            .all(|character| matches!(character, '|' | '-' | '+' | ' '))
+    {
+        return None;
+    }
+    let cells: Vec<&str> = line
+        .trim_matches('|')
+        .split('|')
+        .map(|cell| cell.trim())
+        .collect();
+    let cell = |index: usize| cells.get(index).copied().filter(|cell| !cell.is_empty());
+    let date = parse_org_date(cell(0)?)?;
+    Some(RunEntry {
+        date: date.format(RUN_ENTRY_DATE_FORMAT).to_string(),
+        am: cell(1).map(|am| am.to_string().into()),
+        pm: cell(2).map(|pm| pm.to_string().into()),
+        distance_km: cell(3).and_then(|distance| parse_distance_with_unit(distance).ok()),
+        duration_seconds: None,
+    })
+}
+
+/// `RUN_ENTRY_DATE_FORMAT` or an org timestamp like `<2026-10-13 Tue>` or `2026-10-13`
+fn parse_org_date(text: &str) -> Option<NaiveDate> {
+    if let Ok(date) = validate_date_string(text, RUN_ENTRY_DATE_FORMAT) {
+        return Some(date);
+    }
+    let timestamp = text.trim_matches(|character| matches!(character, '<' | '>' | '[' | ']'));
+    let iso_date = timestamp.split_whitespace().next()?;
+    validate_date_string(iso_date, "%Y-%m-%d").ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::panel_functionalities::running_schedule::ActivityType;
+
+    fn sample_schedule() -> Vec<RunEntry> {
+        vec![
+            RunEntry {
+                date: "03/01/2024".to_string(),
+                am: Some(ActivityType::Easy),
+                pm: Some(ActivityType::Rest),
+                distance_km: Some(5.5),
+                duration_seconds: None,
+            },
+            RunEntry {
+                date: "03/02/2024".to_string(),
+                am: Some(ActivityType::CrossTrain("bike".to_string())),
+                pm: None,
+                distance_km: None,
+                duration_seconds: None,
+            },
+        ]
+    }
+
+    #[test]
+    fn data_format_parses_case_insensitively() {
+        assert_eq!(" TOML ".parse::<DataFormat>(), Ok(DataFormat::Toml));
+        assert_eq!("org".parse::<DataFormat>(), Ok(DataFormat::Org));
+        assert!("yaml".parse::<DataFormat>().is_err());
+    }
+
+    #[test]
+    fn toml_schedule_round_trips() {
+        let path = std::env::temp_dir().join("startup_tui_toml_schedule_round_trip.toml");
+        save_schedule_to_toml(&path, &sample_schedule()).unwrap();
+        let loaded_schedule = load_schedule_from_toml(&path);
+        let _ = fs::remove_file(&path);
+        assert_eq!(loaded_schedule.unwrap(), sample_schedule());
+    }
+
+    #[test]
+    fn org_table_row_round_trips() {
+        for run_entry in sample_schedule() {
+            let row = format_org_table_row(&run_entry);
+            assert_eq!(parse_org_table_row(&row), Some(run_entry));
+        }
+        assert_eq!(
+            format_org_table_row(&sample_schedule()[0]),
+            "| <2024-03-01 Fri> | easy | rest | 5.5 |"
+        );
+    }
+
+    #[test]
+    fn org_table_header_and_separator_are_not_rows() {
+        assert_eq!(parse_org_table_row(ORG_TABLE_HEADER), None);
+        assert_eq!(parse_org_table_row(ORG_TABLE_SEPARATOR), None);
+        assert_eq!(parse_org_table_row("* Training"), None);
+    }
+
+    #[test]
+    fn save_schedule_to_org_keeps_the_surrounding_text() {
+        let path = std::env::temp_dir().join("startup_tui_org_schedule_round_trip.org");
+        fs::write(
+            &path,
+            format!(
+                "* Training\n{}\n{}\n| 01/01/2024 | long | | 20 |\nnotes below\n",
+                ORG_TABLE_HEADER, ORG_TABLE_SEPARATOR
+            ),
+        )
+        .unwrap();
+        save_schedule_to_org(&path, &sample_schedule()).unwrap();
+        let org_text = fs::read_to_string(&path);
+        let loaded_schedule = load_schedule_from_org(&path);
+        let _ = fs::remove_file(&path);
+        let org_text = org_text.unwrap();
+        assert!(org_text.starts_with("* Training\n"));
+        assert!(org_text.ends_with("\nnotes below\n"));
+        assert_eq!(loaded_schedule.unwrap(), sample_schedule());
+    }
+}
@@ -0,0 +1,39 @@
+const EARTH_RADIUS_KM: f64 = 6371.0;
+
+/// Great-circle distance in kilometres between two points given in degrees
+pub fn haversine_distance(lat1: f64, lon1: f64, lat2: f64, lon2: f64) -> f64 {
+    let delta_latitude = (lat2 - lat1).to_radians();
+    let delta_longitude = (lon2 - lon1).to_radians();
+    let a = (delta_latitude / 2.0).sin().powi(2)
+        + lat1.to_radians().cos() * lat2.to_radians().cos() * (delta_longitude / 2.0).sin().powi(2);
+    2.0 * EARTH_RADIUS_KM * a.sqrt().asin()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_within_one_percent(actual_km: f64, expected_km: f64) {
+        assert!(
+            (actual_km - expected_km).abs() <= expected_km * 0.01,
+            "{actual_km} km is not within 1% of {expected_km} km"
+        );
+    }
+
+    #[test]
+    fn haversine_distance_matches_known_city_distances() {
+        // Berlin to Tokyo
+        assert_within_one_percent(haversine_distance(52.52, 13.405, 35.6762, 139.6503), 8920.0);
+        // Paris to London
+        assert_within_one_percent(haversine_distance(48.8566, 2.3522, 51.5074, -0.1278), 344.0);
+    }
+
+    #[test]
+    fn haversine_distance_is_symmetric_and_zero_for_the_same_point() {
+        assert_eq!(haversine_distance(52.52, 13.405, 52.52, 13.405), 0.0);
+        assert_eq!(
+            haversine_distance(52.52, 13.405, 35.6762, 139.6503),
+            haversine_distance(35.6762, 139.6503, 52.52, 13.405)
+        );
+    }
+}
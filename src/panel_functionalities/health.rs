@@ -0,0 +1,103 @@
+use crate::panel_functionalities::running_schedule::{ActivityType, RunEntry};
+use chrono::NaiveDate;
+
+/// MET value the calorie factor is relative to, an easy run costs about 1 kcal per kg and km
+const REFERENCE_MET: f64 = 8.0;
+
+fn metabolic_equivalent(activity_type: &ActivityType) -> f64 {
+    match activity_type {
+        ActivityType::Rest => 0.0,
+        ActivityType::Easy | ActivityType::Long | ActivityType::Other(_) => 8.0,
+        ActivityType::Tempo => 10.0,
+        ActivityType::Interval | ActivityType::Race => 11.0,
+        ActivityType::CrossTrain(_) => 7.0,
+    }
+}
+
+/// Rough energy cost in kcal, `distance_km * weight_kg` scaled by the MET value of the activity
+pub fn estimate_calories(distance_km: f64, weight_kg: f64, activity_type: ActivityType) -> f64 {
+    distance_km * weight_kg * metabolic_equivalent(&activity_type) / REFERENCE_MET
+}
+
+/// Calories of the entry's distance at its most intense session, an easy run if neither
+/// session says otherwise
+pub fn estimate_entry_calories(run_entry: &RunEntry, weight_kg: f64) -> f64 {
+    let Some(distance_km) = run_entry.distance_km else {
+        return 0.0;
+    };
+    let activity_type = [&run_entry.am, &run_entry.pm]
+        .into_iter()
+        .flatten()
+        .filter(|session| **session != ActivityType::Rest)
+        .max_by(|a, b| metabolic_equivalent(a).total_cmp(&metabolic_equivalent(b)))
+        .cloned()
+        .unwrap_or(ActivityType::Easy);
+    estimate_calories(distance_km, weight_kg, activity_type)
+}
+
+/// Summed calorie estimates of the entries dated from `start` to `end`, both inclusive
+pub fn calories_for_period(
+    schedule: &[RunEntry],
+    start: NaiveDate,
+    end: NaiveDate,
+    weight_kg: f64,
+) -> f64 {
+    schedule
+        .iter()
+        .filter(|run_entry| {
+            run_entry
+                .parsed_date()
+                .is_some_and(|date| start <= date && date <= end)
+        })
+        .map(|run_entry| estimate_entry_calories(run_entry, weight_kg))
+        .sum()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn run_entry(date: &str, am: ActivityType, pm: ActivityType, distance_km: f64) -> RunEntry {
+        RunEntry {
+            date: date.to_string(),
+            am: Some(am),
+            pm: Some(pm),
+            distance_km: Some(distance_km),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn estimate_calories_scales_with_the_met_value() {
+        assert_eq!(estimate_calories(10.0, 70.0, ActivityType::Easy), 700.0);
+        assert_eq!(estimate_calories(10.0, 70.0, ActivityType::Tempo), 875.0);
+        assert_eq!(estimate_calories(10.0, 70.0, ActivityType::Interval), 962.5);
+        assert_eq!(estimate_calories(10.0, 70.0, ActivityType::Rest), 0.0);
+    }
+
+    #[test]
+    fn estimate_entry_calories_uses_the_most_intense_session() {
+        let tempo_day = run_entry("05/07/2024", ActivityType::Easy, ActivityType::Tempo, 10.0);
+        assert_eq!(estimate_entry_calories(&tempo_day, 70.0), 875.0);
+        let rest_day = run_entry("05/07/2024", ActivityType::Rest, ActivityType::Rest, 10.0);
+        assert_eq!(estimate_entry_calories(&rest_day, 70.0), 700.0);
+    }
+
+    #[test]
+    fn calories_for_period_includes_both_bounds_only() {
+        let schedule: Vec<RunEntry> = [
+            "05/05/2024",
+            "05/06/2024",
+            "05/09/2024",
+            "05/12/2024",
+            "05/13/2024",
+        ]
+        .into_iter()
+        .map(|date| run_entry(date, ActivityType::Easy, ActivityType::Rest, 10.0))
+        .collect();
+        let start = NaiveDate::from_ymd_opt(2024, 5, 6).unwrap();
+        let end = NaiveDate::from_ymd_opt(2024, 5, 12).unwrap();
+        assert_eq!(calories_for_period(&schedule, start, end, 70.0), 2100.0);
+        assert_eq!(calories_for_period(&schedule, start, start, 70.0), 700.0);
+    }
+}
@@ -0,0 +1,97 @@
+use crate::panel_functionalities::running_schedule::RunEntry;
+use chrono::{Datelike, NaiveDate};
+use ratatui::{
+    layout::{Position, Rect},
+    style::{palette::tailwind, Color, Style},
+    Frame,
+};
+use std::collections::HashMap;
+
+/// Columns taken by one day of the heatmap
+pub const HEATMAP_CELL_WIDTH: u16 = 2;
+/// Weeks a year touches at most, one column each
+pub const HEATMAP_WEEKS: u16 = 53;
+/// Monday to sunday, one row each
+pub const HEATMAP_DAYS_PER_WEEK: u16 = 7;
+const HEATMAP_CELL_SYMBOL: &str = "█";
+const NO_RUN_COLOR: Color = Color::DarkGray;
+/// Light, medium, heavy and peak days, darker for more km
+const HEATMAP_PALETTE: [Color; 4] = [
+    tailwind::GREEN.c300,
+    tailwind::GREEN.c500,
+    tailwind::GREEN.c700,
+    tailwind::GREEN.c900,
+];
+
+/// Week column and weekday row of `date` in the grid of its year
+fn heatmap_cell(date: NaiveDate) -> (u16, u16) {
+    let january_first_offset = date.with_ordinal(1).map_or(0, |january_first| {
+        january_first.weekday().num_days_from_monday()
+    });
+    let column = (date.ordinal0() + january_first_offset) / 7;
+    (column as u16, date.weekday().num_days_from_monday() as u16)
+}
+
+/// Area of the cell of `date` in a heatmap drawn into `area`, `None` if it is cut off
+pub fn heatmap_cell_area(date: NaiveDate, area: Rect) -> Option<Rect> {
+    let (column, row) = heatmap_cell(date);
+    let cell_area = Rect {
+        x: area.x + column * HEATMAP_CELL_WIDTH,
+        y: area.y + row,
+        width: HEATMAP_CELL_WIDTH,
+        height: 1,
+    };
+    (area.intersection(cell_area) == cell_area).then_some(cell_area)
+}
+
+/// Date of `year` whose cell covers the terminal position `(x, y)` of a heatmap in `area`
+pub fn heatmap_date_at(year: i32, area: Rect, x: u16, y: u16) -> Option<NaiveDate> {
+    if !area.contains(Position { x, y }) {
+        return None;
+    }
+    let january_first = NaiveDate::from_ymd_opt(year, 1, 1)?;
+    let day_index = ((x - area.x) / HEATMAP_CELL_WIDTH) as i64 * 7 + (y - area.y) as i64
+        - january_first.weekday().num_days_from_monday() as i64;
+    let date = january_first + chrono::Duration::days(day_index);
+    (day_index >= 0 && date.year() == year).then_some(date)
+}
+
+fn heatmap_color(distance_km: f64, peak_distance_km: f64) -> Color {
+    if distance_km <= 0.0 || peak_distance_km <= 0.0 {
+        return NO_RUN_COLOR;
+    }
+    let level = (distance_km / peak_distance_km * HEATMAP_PALETTE.len() as f64).ceil() as usize;
+    HEATMAP_PALETTE[level.clamp(1, HEATMAP_PALETTE.len()) - 1]
+}
+
+/// GitHub style grid of `year`, one column per week and one row per weekday, colored by the
+/// distance of each day relative to the longest day of the year
+pub fn render_calendar_heatmap(schedule: &[RunEntry], year: i32, frame: &mut Frame, area: Rect) {
+    let Some(january_first) = NaiveDate::from_ymd_opt(year, 1, 1) else {
+        return;
+    };
+    let mut daily_distances: HashMap<NaiveDate, f64> = HashMap::new();
+    for run_entry in schedule {
+        if let (Some(date), Some(distance_km)) = (run_entry.parsed_date(), run_entry.distance_km) {
+            if date.year() == year {
+                *daily_distances.entry(date).or_insert(0.0) += distance_km;
+            }
+        }
+    }
+    let peak_distance_km = daily_distances.values().copied().fold(0.0, f64::max);
+    let buffer = frame.buffer_mut();
+    for date in january_first
+        .iter_days()
+        .take_while(|date| date.year() == year)
+    {
+        if let Some(cell_area) = heatmap_cell_area(date, area) {
+            let distance_km = daily_distances.get(&date).copied().unwrap_or(0.0);
+            buffer.set_string(
+                cell_area.x,
+                cell_area.y,
+                HEATMAP_CELL_SYMBOL.repeat(HEATMAP_CELL_WIDTH as usize),
+                Style::default().fg(heatmap_color(distance_km, peak_distance_km)),
+            );
+        }
+    }
+}
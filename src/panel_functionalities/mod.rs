@@ -1,3 +1,12 @@
 pub mod weather;
+pub mod weather_forecast_panel;
 pub mod calendar_todo;
-pub mod sun_state;
\ No newline at end of file
+pub mod geo;
+pub mod sun_state;
+pub mod running_schedule;
+pub mod statistics;
+pub mod health;
+pub mod season;
+pub mod astronomy;
+pub mod data_formats;
+pub mod heatmap;
\ No newline at end of file
@@ -0,0 +1,698 @@
+use crate::log_message;
+use crate::util::{current_week_dates, validate_date_string, WeekStart};
+use chrono::{Datelike, NaiveDate};
+use ratatui::style::{Color, Style};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+
+pub const RUN_ENTRY_DATE_FORMAT: &str = "%m/%d/%Y";
+const CROSS_TRAIN_PREFIX: &str = "cross train: ";
+
+/// Kind of an am or pm session, stored as its display string so older files still load
+#[derive(Debug, Default, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(from = "String", into = "String")]
+pub enum ActivityType {
+    #[default]
+    Rest,
+    Easy,
+    Tempo,
+    Long,
+    Interval,
+    Race,
+    /// The sport, e.g. `cross train: bike`
+    CrossTrain(String),
+    /// Free text that isn't one of the known kinds
+    Other(String),
+}
+
+impl ActivityType {
+    /// Color coding of a session wherever it is shown
+    pub fn apply_style_to_activity(activity: &ActivityType) -> Style {
+        let color = match activity {
+            ActivityType::Rest => Color::DarkGray,
+            ActivityType::Easy => Color::Green,
+            ActivityType::Tempo => Color::Yellow,
+            ActivityType::Long => Color::Blue,
+            ActivityType::Interval => Color::Magenta,
+            ActivityType::Race => Color::Red,
+            ActivityType::CrossTrain(_) => Color::Cyan,
+            ActivityType::Other(_) => Color::White,
+        };
+        Style::default().fg(color)
+    }
+}
+
+impl fmt::Display for ActivityType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ActivityType::Rest => write!(f, "rest"),
+            ActivityType::Easy => write!(f, "easy"),
+            ActivityType::Tempo => write!(f, "tempo"),
+            ActivityType::Long => write!(f, "long"),
+            ActivityType::Interval => write!(f, "interval"),
+            ActivityType::Race => write!(f, "race"),
+            ActivityType::CrossTrain(sport) => write!(f, "{}{}", CROSS_TRAIN_PREFIX, sport),
+            ActivityType::Other(text) => write!(f, "{}", text),
+        }
+    }
+}
+
+/// Known kinds are matched ignoring case, anything else becomes [`ActivityType::Other`]
+impl From<String> for ActivityType {
+    fn from(text: String) -> Self {
+        let lowercase_text = text.trim().to_lowercase();
+        if let Some(sport) = lowercase_text.strip_prefix(CROSS_TRAIN_PREFIX) {
+            return ActivityType::CrossTrain(sport.to_string());
+        }
+        match lowercase_text.as_str() {
+            "rest" => ActivityType::Rest,
+            "easy" => ActivityType::Easy,
+            "tempo" => ActivityType::Tempo,
+            "long" => ActivityType::Long,
+            "interval" | "intervals" => ActivityType::Interval,
+            "race" => ActivityType::Race,
+            _ => ActivityType::Other(text),
+        }
+    }
+}
+
+impl From<ActivityType> for String {
+    fn from(activity_type: ActivityType) -> Self {
+        activity_type.to_string()
+    }
+}
+
+/// One day of `environment_dict["running_schedule"]`
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RunEntry {
+    /// `%m/%d/%Y`
+    pub date: String,
+    pub am: Option<ActivityType>,
+    pub pm: Option<ActivityType>,
+    pub distance_km: Option<f64>,
+    pub duration_seconds: Option<u64>,
+}
+
+impl RunEntry {
+    pub fn parsed_date(&self) -> Option<NaiveDate> {
+        validate_date_string(&self.date, RUN_ENTRY_DATE_FORMAT).ok()
+    }
+}
+
+/// Copy of `entry` on `new_date` with the same sessions, distance and duration are left
+/// empty since they belong to the day that was run
+pub fn copy_entry_to_date(entry: &RunEntry, new_date: NaiveDate) -> RunEntry {
+    RunEntry {
+        date: new_date.format(RUN_ENTRY_DATE_FORMAT).to_string(),
+        am: entry.am.clone(),
+        pm: entry.pm.clone(),
+        distance_km: None,
+        duration_seconds: None,
+    }
+}
+
+/// Inserts a rest entry for every day of the coming week that has no entry yet
+pub fn detect_and_fill_missing_dates(schedule: &mut Vec<RunEntry>) {
+    let current_date = chrono::Local::now().date_naive();
+    for day_increment in 0..7 {
+        let date = current_date + chrono::Duration::days(day_increment);
+        if schedule
+            .iter()
+            .any(|run_entry| run_entry.parsed_date() == Some(date))
+        {
+            continue;
+        }
+        schedule.push(RunEntry {
+            date: date.format(RUN_ENTRY_DATE_FORMAT).to_string(),
+            am: Some(ActivityType::Rest),
+            pm: Some(ActivityType::Rest),
+            distance_km: Some(0.0),
+            ..Default::default()
+        });
+    }
+}
+
+/// Ascending by date, entries with unparseable dates go to the end and are logged
+pub fn sort_running_schedule(schedule: &mut [RunEntry]) {
+    for run_entry in schedule.iter() {
+        if run_entry.parsed_date().is_none() {
+            log_message!(&format!(
+                "running schedule entry has an unparseable date: {}",
+                run_entry.date
+            ));
+        }
+    }
+    schedule.sort_by_key(|run_entry| {
+        let parsed_date = run_entry.parsed_date();
+        (parsed_date.is_none(), parsed_date)
+    });
+}
+
+/// Drops entries dated more than `keep_days` days before today, unparseable dates are kept
+pub fn prune_old_schedule_entries(schedule: &mut Vec<RunEntry>, keep_days: u32) {
+    let oldest_kept_date =
+        chrono::Local::now().date_naive() - chrono::Duration::days(keep_days as i64);
+    schedule.retain(|run_entry| {
+        run_entry
+            .parsed_date()
+            .is_none_or(|date| date >= oldest_kept_date)
+    });
+}
+
+/// Summed `distance_km` of the entries dated within `[start, end]`
+pub fn total_distance_for_date_range(
+    schedule: &[RunEntry],
+    start: NaiveDate,
+    end: NaiveDate,
+) -> f64 {
+    schedule
+        .iter()
+        .filter(|run_entry| {
+            run_entry
+                .parsed_date()
+                .is_some_and(|date| start <= date && date <= end)
+        })
+        // not sum(), which gives -0.0 for an empty range
+        .fold(0.0, |total_km, run_entry| {
+            total_km + run_entry.distance_km.unwrap_or(0.0)
+        })
+}
+
+/// Whether any entry has a non zero `distance_km`, older files only keep flat totals and
+/// planned or filled in rest days have no distance
+pub fn has_logged_distance(schedule: &[RunEntry]) -> bool {
+    schedule.iter().any(|run_entry| {
+        run_entry
+            .distance_km
+            .is_some_and(|distance_km| distance_km > 0.0)
+    })
+}
+
+/// Summed `distance_km` keyed by `YYYY-MM` and by `YYYY`, entries with unparseable dates are skipped
+pub fn distance_totals_by_month_and_year(
+    schedule: &[RunEntry],
+) -> (BTreeMap<String, f64>, BTreeMap<String, f64>) {
+    let mut monthly_totals: BTreeMap<String, f64> = BTreeMap::new();
+    let mut yearly_totals: BTreeMap<String, f64> = BTreeMap::new();
+    for run_entry in schedule {
+        let (Some(date), Some(distance_km)) = (run_entry.parsed_date(), run_entry.distance_km)
+        else {
+            continue;
+        };
+        *monthly_totals
+            .entry(date.format("%Y-%m").to_string())
+            .or_insert(0.0) += distance_km;
+        *yearly_totals
+            .entry(date.format("%Y").to_string())
+            .or_insert(0.0) += distance_km;
+    }
+    (monthly_totals, yearly_totals)
+}
+
+/// Quotes a field when it contains a separator, quote or line break
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+pub fn running_schedule_to_csv(schedule: &[RunEntry]) -> String {
+    let mut csv = String::from("date,am,pm,distance_km,duration_seconds\n");
+    for run_entry in schedule {
+        let row = [
+            csv_field(&run_entry.date),
+            csv_field(
+                &run_entry
+                    .am
+                    .as_ref()
+                    .map_or(String::new(), |item| item.to_string()),
+            ),
+            csv_field(
+                &run_entry
+                    .pm
+                    .as_ref()
+                    .map_or(String::new(), |item| item.to_string()),
+            ),
+            run_entry
+                .distance_km
+                .map_or(String::new(), |item| item.to_string()),
+            run_entry
+                .duration_seconds
+                .map_or(String::new(), |item| item.to_string()),
+        ];
+        csv.push_str(&row.join(","));
+        csv.push('\n');
+    }
+    csv
+}
+
+/// Always quoted, so spreadsheets keep sessions like `10` or `1/2` as text
+fn quoted_csv_field(value: &str) -> String {
+    format!("\"{}\"", value.replace('"', "\"\""))
+}
+
+/// One row per ISO week with the am and pm session of each day from monday to sunday and the
+/// week's distance, for pasting into Google Sheets. Entries with unparseable dates are skipped
+pub fn running_schedule_to_google_sheets_csv(schedule: &[RunEntry]) -> String {
+    let mut weeks: BTreeMap<(i32, u32), (Vec<&RunEntry>, f64)> = BTreeMap::new();
+    for run_entry in schedule {
+        let Some(date) = run_entry.parsed_date() else {
+            continue;
+        };
+        let (week_entries, week_distance_km) = weeks
+            .entry((date.iso_week().year(), date.iso_week().week()))
+            .or_default();
+        week_entries.push(run_entry);
+        *week_distance_km += run_entry.distance_km.unwrap_or(0.0);
+    }
+    let mut header = vec![quoted_csv_field("week")];
+    for weekday in ["mon", "tue", "wed", "thu", "fri", "sat", "sun"] {
+        header.push(quoted_csv_field(&format!("{} am", weekday)));
+        header.push(quoted_csv_field(&format!("{} pm", weekday)));
+    }
+    header.push(quoted_csv_field("total_km"));
+    let mut csv = header.join(",") + "\n";
+    for ((iso_year, iso_week), (week_entries, week_distance_km)) in weeks {
+        let mut sessions = vec![String::new(); 14];
+        for run_entry in week_entries {
+            let Some(date) = run_entry.parsed_date() else {
+                continue;
+            };
+            let weekday_index = date.weekday().num_days_from_monday() as usize;
+            for (session_index, session) in [&run_entry.am, &run_entry.pm].into_iter().enumerate() {
+                if let Some(session) = session {
+                    sessions[weekday_index * 2 + session_index] = session.to_string();
+                }
+            }
+        }
+        let mut row = vec![quoted_csv_field(&format!("{}-W{:02}", iso_year, iso_week))];
+        row.extend(sessions.iter().map(|session| quoted_csv_field(session)));
+        row.push(format!("{:.1}", week_distance_km));
+        csv.push_str(&row.join(","));
+        csv.push('\n');
+    }
+    csv
+}
+
+/// One all-day event per entry, entries with unparseable dates are skipped
+pub fn running_schedule_to_ics(schedule: &[RunEntry]) -> String {
+    let timestamp = chrono::Utc::now().format("%Y%m%dT%H%M%SZ").to_string();
+    let mut ics_lines = vec![
+        "BEGIN:VCALENDAR".to_string(),
+        "VERSION:2.0".to_string(),
+        "PRODID:-//startup_tui//running schedule//EN".to_string(),
+    ];
+    for run_entry in schedule {
+        let Some(date) = run_entry.parsed_date() else {
+            continue;
+        };
+        let ics_date = date.format("%Y%m%d").to_string();
+        ics_lines.extend([
+            "BEGIN:VEVENT".to_string(),
+            format!("UID:{}-run@startup_tui", ics_date),
+            format!("DTSTAMP:{}", timestamp),
+            format!("DTSTART;VALUE=DATE:{}", ics_date),
+            format!(
+                "SUMMARY:AM {} / PM {}",
+                run_entry.am.clone().unwrap_or_default(),
+                run_entry.pm.clone().unwrap_or_default()
+            ),
+            "END:VEVENT".to_string(),
+        ]);
+    }
+    ics_lines.push("END:VCALENDAR".to_string());
+    ics_lines.join("\r\n") + "\r\n"
+}
+
+/// Date at the start of a log line as `YYYY-MM-DD`, `MM/DD/YYYY` or `<month> <day>` in the
+/// current year, with an optional trailing `:`, and the rest of the line
+fn split_log_line_date(line: &str) -> Option<(NaiveDate, &str)> {
+    let mut tokens = line.splitn(2, char::is_whitespace);
+    let first_token = tokens.next()?.trim_end_matches(':');
+    let rest = tokens.next().unwrap_or("");
+    for date_format in ["%Y-%m-%d", RUN_ENTRY_DATE_FORMAT] {
+        if let Ok(date) = validate_date_string(first_token, date_format) {
+            return Some((date, rest));
+        }
+    }
+    let mut tokens = rest.splitn(2, char::is_whitespace);
+    let day = tokens.next()?.trim_end_matches(':');
+    let rest = tokens.next().unwrap_or("");
+    let year = chrono::Local::now().year();
+    // %B also accepts abbreviated month names when parsing
+    validate_date_string(&format!("{} {} {}", first_token, day, year), "%B %d %Y")
+        .ok()
+        .map(|date| (date, rest))
+}
+
+/// Kilometres of the first `10k`, `12km` or `12.5 km` style distance in `text`, and `text`
+/// without that distance
+fn split_log_distance_km(text: &str) -> (Option<f64>, String) {
+    let tokens: Vec<&str> = text.split_whitespace().collect();
+    for (index, token) in tokens.iter().enumerate() {
+        let lowercase_token = token.to_lowercase();
+        let (number_text, token_count) = match lowercase_token
+            .strip_suffix("km")
+            .or_else(|| lowercase_token.strip_suffix('k'))
+        {
+            Some(number_text) => (number_text, 1),
+            None if tokens.get(index + 1).is_some_and(|next_token| {
+                matches!(next_token.to_lowercase().as_str(), "km" | "k")
+            }) =>
+            {
+                (lowercase_token.as_str(), 2)
+            }
+            None => continue,
+        };
+        if let Ok(distance_km) = number_text.parse::<f64>() {
+            let remaining_tokens: Vec<&str> = tokens[..index]
+                .iter()
+                .chain(&tokens[index + token_count..])
+                .copied()
+                .collect();
+            return (Some(distance_km), remaining_tokens.join(" "));
+        }
+    }
+    (None, tokens.join(" "))
+}
+
+/// Structured entry from a free text log line like `2024-05-10 easy 10k` or
+/// `May 10: AM easy 12km PM rest`, `None` for lines without a date and a description
+pub fn parse_run_log_line(line: &str) -> Option<RunEntry> {
+    let (date, description) = split_log_line_date(line.trim())?;
+    let description = description.trim();
+    if description.is_empty() {
+        return None;
+    }
+    let mut am_words: Vec<&str> = vec![];
+    let mut pm_words: Vec<&str> = vec![];
+    let mut in_pm_part = false;
+    for word in description.split_whitespace() {
+        match word.to_uppercase().trim_end_matches(':') {
+            "AM" => in_pm_part = false,
+            "PM" => in_pm_part = true,
+            _ if in_pm_part => pm_words.push(word),
+            _ => am_words.push(word),
+        }
+    }
+    let (am_distance_km, am) = split_log_distance_km(&am_words.join(" "));
+    let (pm_distance_km, pm) = split_log_distance_km(&pm_words.join(" "));
+    let distances_km: Vec<f64> = [am_distance_km, pm_distance_km]
+        .into_iter()
+        .flatten()
+        .collect();
+    Some(RunEntry {
+        date: date.format(RUN_ENTRY_DATE_FORMAT).to_string(),
+        am: (!am.is_empty()).then(|| ActivityType::from(am)),
+        pm: (!pm.is_empty()).then(|| ActivityType::from(pm)),
+        distance_km: (!distances_km.is_empty()).then(|| distances_km.iter().sum()),
+        duration_seconds: None,
+    })
+}
+
+/// Which entry survives when two schedule entries share a date
+#[derive(Debug, Default, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ConflictStrategy {
+    /// The entry appended last, i.e. the imported one
+    #[default]
+    PreferImported,
+    PreferExisting,
+}
+
+/// Keeps one entry per date as picked by `strategy`, returns how many were removed. Dates
+/// are compared parsed so `5/1/2024` and `05/01/2024` are the same day, unparseable dates
+/// only match the exact same text
+pub fn deduplicate_schedule_entries(
+    schedule: &mut Vec<RunEntry>,
+    strategy: ConflictStrategy,
+) -> usize {
+    let date_keys: Vec<Result<NaiveDate, String>> = schedule
+        .iter()
+        .map(|run_entry| {
+            run_entry
+                .parsed_date()
+                .ok_or_else(|| run_entry.date.clone())
+        })
+        .collect();
+    let mut kept_index_by_date: HashMap<&Result<NaiveDate, String>, usize> = HashMap::new();
+    for (index, date_key) in date_keys.iter().enumerate() {
+        match strategy {
+            ConflictStrategy::PreferImported => {
+                kept_index_by_date.insert(date_key, index);
+            }
+            ConflictStrategy::PreferExisting => {
+                kept_index_by_date.entry(date_key).or_insert(index);
+            }
+        }
+    }
+    let original_length = schedule.len();
+    let mut index = 0;
+    schedule.retain(|_| {
+        let keep = kept_index_by_date[&date_keys[index]] == index;
+        index += 1;
+        keep
+    });
+    original_length - schedule.len()
+}
+
+/// Dates with more than one entry, oldest first, for warning before one of them is shadowed
+pub fn detect_duplicate_running_entries(schedule: &[RunEntry]) -> Vec<NaiveDate> {
+    let mut entry_count_by_date: BTreeMap<NaiveDate, usize> = BTreeMap::new();
+    for date in schedule.iter().filter_map(RunEntry::parsed_date) {
+        *entry_count_by_date.entry(date).or_insert(0) += 1;
+    }
+    entry_count_by_date
+        .into_iter()
+        .filter(|(_, entry_count)| *entry_count > 1)
+        .map(|(date, _)| date)
+        .collect()
+}
+
+/// Appends the imported entries and resolves dates present twice with `strategy`,
+/// returns how many new dates were added
+pub fn merge_run_entries(
+    schedule: &mut Vec<RunEntry>,
+    imported_entries: Vec<RunEntry>,
+    strategy: ConflictStrategy,
+) -> usize {
+    // duplicates already in the schedule go first so they aren't counted against the import
+    let mut removed_entry_count = deduplicate_schedule_entries(schedule, strategy);
+    let deduplicated_length = schedule.len();
+    schedule.extend(imported_entries);
+    removed_entry_count += deduplicate_schedule_entries(schedule, strategy);
+    log_message!(&format!(
+        "import removed {} duplicate schedule entries ({:?})",
+        removed_entry_count, strategy
+    ));
+    schedule.len() - deduplicated_length
+}
+
+/// Entries whose `am` or `pm` session contains `session_type`, ignoring case
+pub fn filter_schedule_by_session_type<'a>(
+    schedule: &'a [RunEntry],
+    session_type: &str,
+) -> Vec<&'a RunEntry> {
+    let session_type = session_type.to_lowercase();
+    schedule
+        .iter()
+        .filter(|run_entry| {
+            [&run_entry.am, &run_entry.pm].iter().any(|session| {
+                session.as_ref().is_some_and(|session| {
+                    session.to_string().to_lowercase().contains(&session_type)
+                })
+            })
+        })
+        .collect()
+}
+
+/// Entries of the current week whose am and pm sessions are both missing or `rest`
+pub fn count_rest_days_in_week(schedule: &[RunEntry], week_start: WeekStart) -> u32 {
+    let week_dates = current_week_dates(week_start);
+    let is_rest = |session: &Option<ActivityType>| {
+        session
+            .as_ref()
+            .is_none_or(|session| *session == ActivityType::Rest)
+    };
+    schedule
+        .iter()
+        .filter(|run_entry| {
+            run_entry
+                .parsed_date()
+                .is_some_and(|date| week_dates.contains(&date))
+                && is_rest(&run_entry.am)
+                && is_rest(&run_entry.pm)
+        })
+        .count() as u32
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn run_entry(date: &str, distance_km: Option<f64>) -> RunEntry {
+        RunEntry {
+            date: date.to_string(),
+            am: Some(ActivityType::Easy),
+            distance_km,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn total_distance_for_date_range_sums_the_inclusive_range() {
+        let schedule = vec![
+            run_entry("02/29/2024", Some(3.0)),
+            run_entry("03/01/2024", Some(5.5)),
+            run_entry("03/02/2024", None),
+            run_entry("03/03/2024", Some(10.0)),
+            run_entry("not a date", Some(100.0)),
+        ];
+        let date = |day| NaiveDate::from_ymd_opt(2024, 3, day).unwrap();
+        assert_eq!(
+            total_distance_for_date_range(&schedule, date(1), date(3)),
+            15.5
+        );
+        assert_eq!(
+            total_distance_for_date_range(&schedule, date(2), date(2)),
+            0.0
+        );
+        assert_eq!(
+            total_distance_for_date_range(&schedule, date(3), date(1)),
+            0.0
+        );
+        assert!(total_distance_for_date_range(&[], date(1), date(3)).is_sign_positive());
+    }
+
+    #[test]
+    fn has_logged_distance_ignores_empty_and_zero_distances() {
+        assert!(!has_logged_distance(&[]));
+        assert!(!has_logged_distance(&[
+            run_entry("03/01/2024", None),
+            run_entry("03/02/2024", Some(0.0)),
+        ]));
+        assert!(has_logged_distance(&[run_entry("03/01/2024", Some(0.1))]));
+    }
+
+    #[test]
+    fn detect_and_fill_missing_dates_adds_rest_days_with_zero_distance() {
+        let today = chrono::Local::now().date_naive();
+        let tomorrow = (today + chrono::Duration::days(1))
+            .format(RUN_ENTRY_DATE_FORMAT)
+            .to_string();
+        let mut schedule = vec![run_entry(&tomorrow, Some(8.0))];
+        detect_and_fill_missing_dates(&mut schedule);
+        assert_eq!(schedule.len(), 7);
+        assert_eq!(schedule[0], run_entry(&tomorrow, Some(8.0)));
+        for filled_entry in &schedule[1..] {
+            assert_eq!(filled_entry.am, Some(ActivityType::Rest));
+            assert_eq!(filled_entry.distance_km, Some(0.0));
+        }
+        assert!(!has_logged_distance(&schedule[1..]));
+    }
+
+    #[test]
+    fn parse_run_log_line_reads_iso_dated_lines() {
+        assert_eq!(
+            parse_run_log_line("2024-05-10 easy 10k"),
+            Some(RunEntry {
+                date: "05/10/2024".to_string(),
+                am: Some(ActivityType::Easy),
+                distance_km: Some(10.0),
+                ..Default::default()
+            })
+        );
+    }
+
+    #[test]
+    fn parse_run_log_line_splits_am_and_pm_sessions() {
+        let year = chrono::Local::now().year();
+        assert_eq!(
+            parse_run_log_line("May 10: AM easy 12km PM rest"),
+            Some(RunEntry {
+                date: format!("05/10/{}", year),
+                am: Some(ActivityType::Easy),
+                pm: Some(ActivityType::Rest),
+                distance_km: Some(12.0),
+                ..Default::default()
+            })
+        );
+        assert_eq!(
+            parse_run_log_line("05/11/2024 AM tempo 8.5 km PM easy 4k")
+                .and_then(|run_entry| run_entry.distance_km),
+            Some(12.5)
+        );
+    }
+
+    #[test]
+    fn parse_run_log_line_skips_lines_without_a_date() {
+        assert_eq!(parse_run_log_line("went for a walk"), None);
+        assert_eq!(parse_run_log_line("2024-05-10"), None);
+    }
+
+    #[test]
+    fn deduplicate_schedule_entries_prefers_imported_entries() {
+        let mut schedule = vec![
+            run_entry("05/01/2024", Some(5.0)),
+            run_entry("05/02/2024", Some(6.0)),
+            run_entry("5/1/2024", Some(10.0)),
+            run_entry("05/01/2024", Some(12.0)),
+        ];
+        assert_eq!(
+            deduplicate_schedule_entries(&mut schedule, ConflictStrategy::PreferImported),
+            2
+        );
+        assert_eq!(
+            schedule,
+            vec![
+                run_entry("05/02/2024", Some(6.0)),
+                run_entry("05/01/2024", Some(12.0)),
+            ]
+        );
+    }
+
+    #[test]
+    fn deduplicate_schedule_entries_prefers_existing_entries() {
+        let mut schedule = vec![
+            run_entry("05/01/2024", Some(5.0)),
+            run_entry("not a date", Some(1.0)),
+            run_entry("5/1/2024", Some(10.0)),
+            run_entry("not a date", Some(2.0)),
+            run_entry("Not a date", Some(3.0)),
+        ];
+        assert_eq!(
+            deduplicate_schedule_entries(&mut schedule, ConflictStrategy::PreferExisting),
+            2
+        );
+        assert_eq!(
+            schedule,
+            vec![
+                run_entry("05/01/2024", Some(5.0)),
+                run_entry("not a date", Some(1.0)),
+                run_entry("Not a date", Some(3.0)),
+            ]
+        );
+    }
+
+    #[test]
+    fn deduplicate_schedule_entries_without_duplicates() {
+        let mut schedule = vec![
+            run_entry("05/01/2024", Some(5.0)),
+            run_entry("05/02/2024", None),
+        ];
+        for strategy in [
+            ConflictStrategy::PreferImported,
+            ConflictStrategy::PreferExisting,
+        ] {
+            assert_eq!(deduplicate_schedule_entries(&mut schedule, strategy), 0);
+            assert_eq!(schedule.len(), 2);
+        }
+        assert_eq!(
+            deduplicate_schedule_entries(&mut vec![], ConflictStrategy::PreferImported),
+            0
+        );
+    }
+}
@@ -0,0 +1,50 @@
+use chrono::Datelike;
+use serde::{Deserialize, Serialize};
+
+/// Half of the globe the user lives in, `settings.hemisphere` in the config
+#[derive(Debug, Default, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Hemisphere {
+    #[default]
+    Northern,
+    Southern,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Season {
+    Spring,
+    Summer,
+    Autumn,
+    Winter,
+}
+
+impl Season {
+    pub fn icon(&self) -> &'static str {
+        match self {
+            Season::Spring => "🌸",
+            Season::Summer => "🌞",
+            Season::Autumn => "🍂",
+            Season::Winter => "❄",
+        }
+    }
+}
+
+/// Meteorological season of the current month, spring starting in march up north and in
+/// september down south
+pub fn get_current_season(hemisphere: Hemisphere) -> Season {
+    let northern_season = match chrono::Local::now().month() {
+        3..=5 => Season::Spring,
+        6..=8 => Season::Summer,
+        9..=11 => Season::Autumn,
+        _ => Season::Winter,
+    };
+    match hemisphere {
+        Hemisphere::Northern => northern_season,
+        Hemisphere::Southern => match northern_season {
+            Season::Spring => Season::Autumn,
+            Season::Summer => Season::Winter,
+            Season::Autumn => Season::Spring,
+            Season::Winter => Season::Summer,
+        },
+    }
+}
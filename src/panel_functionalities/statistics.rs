@@ -0,0 +1,751 @@
+use crate::panel_functionalities::running_schedule::{
+    total_distance_for_date_range, ActivityType, RunEntry,
+};
+use crate::panel_functionalities::weather::CityWeather;
+use crate::util::{current_week_dates, render_sparkline_from_values, WeekStart};
+use chrono::{Datelike, NaiveDate};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeSet, HashMap};
+
+/// Days the chronic training load of the form averages over
+pub const CHRONIC_TRAINING_LOAD_DAYS: u32 = 42;
+/// Days the acute training load averages over
+pub const ACUTE_TRAINING_LOAD_DAYS: u32 = 7;
+/// Days the average [`recommend_rest_or_run`] compares the acute training load to
+const FATIGUE_AVERAGE_DAYS: u32 = 28;
+
+/// When [`recommend_rest_or_run`] backs off, `settings.fatigue_thresholds` in the config
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct FatigueThresholds {
+    /// Acute load as a multiple of the 28 day average above which the body is tired
+    pub load_ratio: f64,
+    /// Consecutive active days above which the body is tired
+    pub streak_days: u32,
+}
+
+impl Default for FatigueThresholds {
+    fn default() -> Self {
+        FatigueThresholds {
+            load_ratio: 1.1,
+            streak_days: 6,
+        }
+    }
+}
+
+/// Distance of each of the last `window_days` days up to today, oldest first, 0 for days
+/// without an entry
+fn daily_distances(schedule: &[RunEntry], window_days: u32) -> Vec<f64> {
+    let today = chrono::Local::now().date_naive();
+    (0..window_days)
+        .rev()
+        .map(|days_ago| {
+            let date = today - chrono::Duration::days(days_ago as i64);
+            total_distance_for_date_range(schedule, date, date)
+        })
+        .collect()
+}
+
+/// Foster's training monotony over the last `window_days` days, mean daily distance divided by
+/// its standard deviation. 0 without any distance, infinite when every day is the same
+pub fn compute_training_monotony(schedule: &[RunEntry], window_days: u32) -> f64 {
+    let daily_distances = daily_distances(schedule, window_days);
+    if daily_distances.is_empty() {
+        return 0.0;
+    }
+    let mean = daily_distances.iter().sum::<f64>() / daily_distances.len() as f64;
+    if mean == 0.0 {
+        return 0.0;
+    }
+    let variance = daily_distances
+        .iter()
+        .map(|distance| (distance - mean).powi(2))
+        .sum::<f64>()
+        / daily_distances.len() as f64;
+    mean / variance.sqrt()
+}
+
+/// Mean daily distance over the last `days` days, [`CHRONIC_TRAINING_LOAD_DAYS`] gives the
+/// chronic and [`ACUTE_TRAINING_LOAD_DAYS`] the acute training load
+pub fn rolling_average_km(schedule: &[RunEntry], days: u32) -> f64 {
+    if days == 0 {
+        return 0.0;
+    }
+    daily_distances(schedule, days).iter().sum::<f64>() / days as f64
+}
+
+/// One block character per 7 day window of the last `weeks` weeks up to today, oldest first,
+/// scaled by the distance run in it
+pub fn render_running_schedule_as_sparkline(schedule: &[RunEntry], weeks: u32) -> String {
+    let today = chrono::Local::now().date_naive();
+    let weekly_distances: Vec<f64> = (0..weeks)
+        .rev()
+        .map(|weeks_ago| {
+            let end = today - chrono::Duration::weeks(weeks_ago as i64);
+            total_distance_for_date_range(schedule, end - chrono::Duration::days(6), end)
+        })
+        .collect();
+    render_sparkline_from_values(&weekly_distances, weekly_distances.len())
+}
+
+/// Dates with a session that isn't `rest`
+fn active_dates(schedule: &[RunEntry]) -> BTreeSet<NaiveDate> {
+    schedule
+        .iter()
+        .filter(|run_entry| {
+            [&run_entry.am, &run_entry.pm]
+                .into_iter()
+                .flatten()
+                .any(|session| *session != ActivityType::Rest)
+        })
+        .filter_map(RunEntry::parsed_date)
+        .collect()
+}
+
+/// Consecutive active days up to today, or up to yesterday while today has no session yet
+pub fn current_streak(schedule: &[RunEntry]) -> u32 {
+    let active_dates = active_dates(schedule);
+    let today = chrono::Local::now().date_naive();
+    let mut date = if active_dates.contains(&today) {
+        today
+    } else {
+        today - chrono::Duration::days(1)
+    };
+    let mut streak_length = 0;
+    while active_dates.contains(&date) {
+        streak_length += 1;
+        date -= chrono::Duration::days(1);
+    }
+    streak_length
+}
+
+/// Longest run of consecutive days with a non rest session anywhere in the schedule and the
+/// day it ended on, the earliest one on ties. `(0, today)` without any active day
+pub fn detect_longest_streak(schedule: &[RunEntry]) -> (u32, NaiveDate) {
+    let mut longest_streak = (0, chrono::Local::now().date_naive());
+    let mut current_streak_length = 0;
+    let mut previous_date: Option<NaiveDate> = None;
+    for date in active_dates(schedule) {
+        current_streak_length = match previous_date {
+            Some(previous_date) if date.pred_opt() == Some(previous_date) => {
+                current_streak_length + 1
+            }
+            _ => 1,
+        };
+        if current_streak_length > longest_streak.0 {
+            longest_streak = (current_streak_length, date);
+        }
+        previous_date = Some(date);
+    }
+    longest_streak
+}
+
+/// The `n` ISO weeks with the most distance as `(week_number, year, total_km)`, highest first
+pub fn top_n_weekly_distances(schedule: &[RunEntry], n: usize) -> Vec<(u32, i32, f64)> {
+    let mut weekly_distances: HashMap<(u32, i32), f64> = HashMap::new();
+    for run_entry in schedule {
+        if let (Some(date), Some(distance_km)) = (run_entry.parsed_date(), run_entry.distance_km) {
+            let iso_week = date.iso_week();
+            *weekly_distances
+                .entry((iso_week.week(), iso_week.year()))
+                .or_insert(0.0) += distance_km;
+        }
+    }
+    let mut weekly_distances: Vec<(u32, i32, f64)> = weekly_distances
+        .into_iter()
+        .map(|((week_number, year), total_km)| (week_number, year, total_km))
+        .collect();
+    // newer weeks first on ties so the order doesn't depend on the hash map
+    weekly_distances.sort_by(|a, b| {
+        b.2.total_cmp(&a.2)
+            .then_with(|| (b.1, b.0).cmp(&(a.1, a.0)))
+    });
+    weekly_distances.truncate(n);
+    weekly_distances
+}
+
+/// Months of the year of `today` up to its month whose distance until `today` met or exceeded
+/// `monthly_goal`, planned distances of later days don't count
+pub fn months_above_goal(schedule: &[RunEntry], monthly_goal: f64, today: NaiveDate) -> u32 {
+    let mut monthly_distances = vec![0.0; today.month() as usize];
+    for run_entry in schedule {
+        if let (Some(date), Some(distance_km)) = (run_entry.parsed_date(), run_entry.distance_km) {
+            if date.year() == today.year() && date <= today {
+                monthly_distances[date.month0() as usize] += distance_km;
+            }
+        }
+    }
+    monthly_distances
+        .iter()
+        .filter(|distance_km| **distance_km >= monthly_goal)
+        .count() as u32
+}
+
+/// Schedule wide figures of the stats popup, computed once when it opens instead of on
+/// every frame
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct RunningStatistics {
+    /// Length and end date as returned by [`detect_longest_streak`]
+    pub longest_streak: (u32, NaiveDate),
+    /// As returned by [`top_n_weekly_distances`]
+    pub top_weeks: Vec<(u32, i32, f64)>,
+    pub months_above_goal: u32,
+}
+
+impl RunningStatistics {
+    pub fn from_schedule(schedule: &[RunEntry], top_weeks_count: usize, monthly_goal: f64) -> Self {
+        RunningStatistics {
+            longest_streak: detect_longest_streak(schedule),
+            top_weeks: top_n_weekly_distances(schedule, top_weeks_count),
+            months_above_goal: months_above_goal(
+                schedule,
+                monthly_goal,
+                chrono::Local::now().date_naive(),
+            ),
+        }
+    }
+}
+
+/// `"Rest today"` when both `load`, the acute training load, is above the 28 day average by
+/// the load ratio and `current_streak` is above the streak threshold,
+/// `"Easy run"` when only one of them is, `"Train as planned"` otherwise
+pub fn recommend_rest_or_run(
+    schedule: &[RunEntry],
+    current_streak: u32,
+    load: f64,
+    thresholds: &FatigueThresholds,
+) -> &'static str {
+    let average_load = rolling_average_km(schedule, FATIGUE_AVERAGE_DAYS);
+    let load_is_high = average_load > 0.0 && load > average_load * thresholds.load_ratio;
+    let streak_is_long = current_streak > thresholds.streak_days;
+    match (load_is_high, streak_is_long) {
+        (true, true) => "Rest today",
+        (true, false) | (false, true) => "Easy run",
+        (false, false) => "Train as planned",
+    }
+}
+
+/// Distance at the end of the year if `current_yearly_km` keeps growing at its mean daily
+/// pace so far. Today only counts as elapsed once it has distance in `schedule`, so the
+/// projection doesn't drop each morning before the run is logged
+pub fn project_yearly_total(schedule: &[RunEntry], current_yearly_km: f64) -> f64 {
+    let today = chrono::Local::now().date_naive();
+    let today_is_logged = total_distance_for_date_range(schedule, today, today) > 0.0;
+    let elapsed_days = if today_is_logged {
+        today.ordinal()
+    } else {
+        today.ordinal0()
+    };
+    current_yearly_km / elapsed_days.max(1) as f64 * 365.0
+}
+
+/// Change of the current week's distance against the whole previous week in percent, 0 when
+/// nothing was run last week
+pub fn compute_week_over_week_change(schedule: &[RunEntry], week_start: WeekStart) -> f64 {
+    let [week_first_date, .., week_last_date] = current_week_dates(week_start);
+    let current = total_distance_for_date_range(schedule, week_first_date, week_last_date);
+    let previous = total_distance_for_date_range(
+        schedule,
+        week_first_date - chrono::Duration::weeks(1),
+        week_last_date - chrono::Duration::weeks(1),
+    );
+    if previous == 0.0 {
+        return 0.0;
+    }
+    (current - previous) / previous * 100.0
+}
+
+/// Number of non rest am and pm sessions of each kind, all [`ActivityType::Other`] sessions
+/// are counted together under `Other("Other")`
+pub fn count_workouts_by_type(schedule: &[RunEntry]) -> HashMap<ActivityType, u32> {
+    let mut workout_counts: HashMap<ActivityType, u32> = HashMap::new();
+    for session in schedule
+        .iter()
+        .flat_map(|run_entry| [&run_entry.am, &run_entry.pm])
+        .flatten()
+    {
+        let activity_type = match session {
+            ActivityType::Rest => continue,
+            ActivityType::Other(_) => ActivityType::Other("Other".to_string()),
+            _ => session.clone(),
+        };
+        *workout_counts.entry(activity_type).or_insert(0) += 1;
+    }
+    workout_counts
+}
+
+/// Each date with a non rest session that `weather` has a forecast for, paired with that
+/// day's weather
+pub fn aggregate_weather_for_run_days(
+    weather: &CityWeather,
+    schedule: &[RunEntry],
+) -> Vec<(NaiveDate, CityWeather)> {
+    active_dates(schedule)
+        .into_iter()
+        .filter_map(|date| Some((date, weather.for_date(date)?)))
+        .collect()
+}
+
+/// Mean of the daily mid temperatures in °C and of the precipitation sums in mm over the first
+/// day of each of `days`, `None` where no day has a value
+pub fn average_temperature_and_precipitation(days: &[CityWeather]) -> (Option<f32>, Option<f32>) {
+    let mean = |values: Vec<f32>| {
+        (!values.is_empty()).then(|| values.iter().sum::<f32>() / values.len() as f32)
+    };
+    let mid_temperatures = days
+        .iter()
+        .filter_map(|day| {
+            let minimum = day.temperature_2m_min.first().copied().flatten()?;
+            let maximum = day.temperature_2m_max.first().copied().flatten()?;
+            Some((minimum + maximum) / 2.0)
+        })
+        .collect();
+    let precipitation_sums = days
+        .iter()
+        .filter_map(|day| day.precipitation_sum.first().copied().flatten())
+        .collect();
+    (mean(mid_temperatures), mean(precipitation_sums))
+}
+
+/// Days since the latest date up to today with a non rest session, 0 when that is today and
+/// `None` without any
+pub fn days_since_last_run(schedule: &[RunEntry]) -> Option<u64> {
+    let today = chrono::Local::now().date_naive();
+    let last_run_date = active_dates(schedule)
+        .into_iter()
+        .rev()
+        .find(|date| *date <= today)?;
+    Some((today - last_run_date).num_days() as u64)
+}
+
+/// All time bests whose record day falls within `week_dates`, as report lines
+fn personal_records_in_week(schedule: &[RunEntry], week_dates: &[NaiveDate; 7]) -> Vec<String> {
+    let mut personal_records = vec![];
+    let (longest_streak, longest_streak_end) = detect_longest_streak(schedule);
+    if longest_streak > 0 && week_dates.contains(&longest_streak_end) {
+        personal_records.push(format!("Longest streak: {} days", longest_streak));
+    }
+    let longest_run = schedule
+        .iter()
+        .filter_map(|run_entry| Some((run_entry.parsed_date()?, run_entry.distance_km?)))
+        .filter(|(_, distance_km)| *distance_km > 0.0)
+        .max_by(|a, b| a.1.total_cmp(&b.1).then_with(|| b.0.cmp(&a.0)));
+    if let Some((date, distance_km)) = longest_run {
+        if week_dates.contains(&date) {
+            personal_records.push(format!("Longest run: {:.1} km", distance_km));
+        }
+    }
+    let today_iso_week = chrono::Local::now().date_naive().iso_week();
+    if let Some(&(week_number, year, total_km)) = top_n_weekly_distances(schedule, 1).first() {
+        if total_km > 0.0 && (week_number, year) == (today_iso_week.week(), today_iso_week.year()) {
+            personal_records.push(format!("Biggest week: {:.1} km", total_km));
+        }
+    }
+    personal_records
+}
+
+/// Plain text report of the current week for sending to a coach, with the week's distance,
+/// days run, each day's sessions, the forecast weather of the week's days in `weather` and
+/// the personal bests set this week
+pub fn build_weekly_report_text(
+    schedule: &[RunEntry],
+    weather: Option<&CityWeather>,
+    week_start: WeekStart,
+) -> String {
+    let week_dates = current_week_dates(week_start);
+    let [week_first_date, .., week_last_date] = week_dates;
+    let today = chrono::Local::now().date_naive();
+    // planned sessions later this week aren't run yet
+    let days_run = active_dates(schedule)
+        .into_iter()
+        .filter(|date| week_dates.contains(date) && *date <= today)
+        .count();
+    let mut report = vec![
+        format!(
+            "Weekly report {} - {}",
+            week_first_date.format("%Y-%m-%d"),
+            week_last_date.format("%Y-%m-%d")
+        ),
+        String::new(),
+        format!(
+            "Total distance: {:.1} km",
+            total_distance_for_date_range(schedule, week_first_date, week_last_date)
+        ),
+        format!("Days run: {}/7", days_run),
+        String::new(),
+        "Sessions:".to_string(),
+    ];
+    for date in week_dates {
+        let sessions: Vec<String> = schedule
+            .iter()
+            .filter(|run_entry| run_entry.parsed_date() == Some(date))
+            .flat_map(|run_entry| {
+                [("am", &run_entry.am), ("pm", &run_entry.pm)]
+                    .into_iter()
+                    .filter_map(|(part_of_day, session)| {
+                        Some(format!("{} {}", part_of_day, session.as_ref()?))
+                    })
+            })
+            .collect();
+        let sessions = if sessions.is_empty() {
+            "-".to_string()
+        } else {
+            sessions.join(", ")
+        };
+        report.push(format!("  {}: {}", date.format("%a %m-%d"), sessions));
+    }
+    report.push(String::new());
+    let week_weather: Vec<CityWeather> = weather
+        .map(|weather| {
+            week_dates
+                .iter()
+                .filter_map(|date| weather.for_date(*date))
+                .collect()
+        })
+        .unwrap_or_default();
+    if week_weather.is_empty() {
+        report.push("Weather: no forecast for this week".to_string());
+    } else {
+        let (average_temperature, _) = average_temperature_and_precipitation(&week_weather);
+        let total_precipitation: f32 = week_weather
+            .iter()
+            .filter_map(|day| day.precipitation_sum.first().copied().flatten())
+            .sum();
+        report.push(format!(
+            "Weather ({} forecast days): avg {}, {:.1} mm rain",
+            week_weather.len(),
+            average_temperature.map_or("-".to_string(), |temperature| format!(
+                "{:.1} °C",
+                temperature
+            )),
+            total_precipitation
+        ));
+    }
+    report.push(String::new());
+    report.push("PRs:".to_string());
+    let personal_records = personal_records_in_week(schedule, &week_dates);
+    if personal_records.is_empty() {
+        report.push("  none this week".to_string());
+    }
+    report.extend(
+        personal_records
+            .into_iter()
+            .map(|personal_record| format!("  {}", personal_record)),
+    );
+    report.join("\n") + "\n"
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::panel_functionalities::running_schedule::RUN_ENTRY_DATE_FORMAT;
+
+    fn run_entry(date: NaiveDate, am: ActivityType, distance_km: Option<f64>) -> RunEntry {
+        RunEntry {
+            date: date.format(RUN_ENTRY_DATE_FORMAT).to_string(),
+            am: Some(am),
+            pm: None,
+            distance_km,
+            duration_seconds: None,
+        }
+    }
+
+    fn days_ago(days: i64) -> NaiveDate {
+        chrono::Local::now().date_naive() - chrono::Duration::days(days)
+    }
+
+    #[test]
+    fn compute_training_monotony_matches_a_known_dataset() {
+        // 2, 4, 2 and 4 km, mean 3 and standard deviation 1
+        let schedule: Vec<RunEntry> = [(3, 2.0), (2, 4.0), (1, 2.0), (0, 4.0)]
+            .into_iter()
+            .map(|(days, distance_km)| {
+                run_entry(days_ago(days), ActivityType::Easy, Some(distance_km))
+            })
+            .collect();
+        assert!((compute_training_monotony(&schedule, 4) - 3.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn compute_training_monotony_counts_missing_days_as_zero() {
+        // 0, 0, 0 and 4 km, mean 1 and standard deviation sqrt(3)
+        let schedule = vec![run_entry(days_ago(0), ActivityType::Long, Some(4.0))];
+        assert!((compute_training_monotony(&schedule, 4) - 1.0 / 3f64.sqrt()).abs() < 1e-9);
+    }
+
+    #[test]
+    fn compute_training_monotony_without_distance_or_variation() {
+        assert_eq!(compute_training_monotony(&[], 7), 0.0);
+        assert_eq!(compute_training_monotony(&[], 0), 0.0);
+        let schedule: Vec<RunEntry> = (0..3)
+            .map(|days| run_entry(days_ago(days), ActivityType::Easy, Some(5.0)))
+            .collect();
+        assert!(compute_training_monotony(&schedule, 3).is_infinite());
+    }
+
+    #[test]
+    fn detect_longest_streak_finds_the_best_streak_in_a_fixture() {
+        let date = |day| NaiveDate::from_ymd_opt(2023, 9, day).unwrap();
+        let mut schedule = vec![
+            // a 3 day streak ending on the 3rd
+            run_entry(date(1), ActivityType::Easy, None),
+            run_entry(date(2), ActivityType::Tempo, None),
+            run_entry(date(3), ActivityType::Easy, None),
+            // a rest day splits the 10th to the 17th into 4 and 3 days
+            run_entry(date(10), ActivityType::Long, None),
+            run_entry(date(11), ActivityType::Easy, None),
+            run_entry(date(12), ActivityType::Interval, None),
+            run_entry(date(13), ActivityType::Easy, None),
+            run_entry(date(14), ActivityType::Rest, None),
+            run_entry(date(15), ActivityType::Easy, None),
+            run_entry(date(16), ActivityType::CrossTrain("bike".to_string()), None),
+            run_entry(date(17), ActivityType::Race, None),
+        ];
+        // the schedule doesn't have to be sorted
+        schedule.reverse();
+        assert_eq!(detect_longest_streak(&schedule), (4, date(13)));
+    }
+
+    #[test]
+    fn detect_longest_streak_keeps_the_earliest_on_ties() {
+        let date = |day| NaiveDate::from_ymd_opt(2023, 12, day).unwrap();
+        let schedule = vec![
+            run_entry(date(1), ActivityType::Easy, None),
+            run_entry(date(2), ActivityType::Easy, None),
+            run_entry(date(30), ActivityType::Easy, None),
+            run_entry(date(31), ActivityType::Easy, None),
+        ];
+        assert_eq!(detect_longest_streak(&schedule), (2, date(2)));
+    }
+
+    #[test]
+    fn detect_longest_streak_without_active_days() {
+        let rest_only = vec![run_entry(days_ago(1), ActivityType::Rest, None)];
+        for schedule in [vec![], rest_only] {
+            assert_eq!(
+                detect_longest_streak(&schedule),
+                (0, chrono::Local::now().date_naive())
+            );
+        }
+    }
+
+    #[test]
+    fn build_weekly_report_text_summarizes_the_current_week() {
+        let week_dates = current_week_dates(WeekStart::Monday);
+        let schedule = vec![
+            run_entry(week_dates[0], ActivityType::Easy, Some(10.0)),
+            run_entry(
+                week_dates[0] - chrono::Duration::weeks(1),
+                ActivityType::Long,
+                Some(8.0),
+            ),
+        ];
+        let weather = CityWeather {
+            time: vec![week_dates[0].format("%Y-%m-%d").to_string()],
+            temperature_2m_min: vec![Some(10.0)],
+            temperature_2m_max: vec![Some(20.0)],
+            precipitation_sum: vec![Some(2.5)],
+            ..Default::default()
+        };
+        let report = build_weekly_report_text(&schedule, Some(&weather), WeekStart::Monday);
+        let lines: Vec<&str> = report.lines().collect();
+        assert_eq!(
+            lines[0],
+            format!(
+                "Weekly report {} - {}",
+                week_dates[0].format("%Y-%m-%d"),
+                week_dates[6].format("%Y-%m-%d")
+            )
+        );
+        assert!(lines.contains(&"Total distance: 10.0 km"));
+        assert!(lines.contains(&"Days run: 1/7"));
+        assert!(
+            lines.contains(&format!("  {}: am easy", week_dates[0].format("%a %m-%d")).as_str())
+        );
+        assert!(lines.contains(&format!("  {}: -", week_dates[1].format("%a %m-%d")).as_str()));
+        assert!(lines.contains(&"Weather (1 forecast days): avg 15.0 °C, 2.5 mm rain"));
+        assert!(lines.contains(&"  Longest run: 10.0 km"));
+    }
+
+    #[test]
+    fn build_weekly_report_text_without_runs_or_forecast() {
+        let report = build_weekly_report_text(&[], None, WeekStart::Sunday);
+        let lines: Vec<&str> = report.lines().collect();
+        assert!(lines.contains(&"Total distance: 0.0 km"));
+        assert!(lines.contains(&"Days run: 0/7"));
+        assert!(lines.contains(&"Weather: no forecast for this week"));
+        assert!(lines.contains(&"  none this week"));
+    }
+
+    #[test]
+    fn running_statistics_from_schedule_keeps_the_longest_streak() {
+        let date = |day| NaiveDate::from_ymd_opt(2023, 9, day).unwrap();
+        let schedule: Vec<RunEntry> = (1..=15)
+            .map(|day| run_entry(date(day), ActivityType::Easy, Some(10.0)))
+            .collect();
+        let running_statistics = RunningStatistics::from_schedule(&schedule, 2, 100.0);
+        assert_eq!(running_statistics.longest_streak, (15, date(15)));
+        assert_eq!(running_statistics.top_weeks.len(), 2);
+        assert_eq!(
+            running_statistics,
+            RunningStatistics {
+                longest_streak: detect_longest_streak(&schedule),
+                top_weeks: top_n_weekly_distances(&schedule, 2),
+                months_above_goal: months_above_goal(
+                    &schedule,
+                    100.0,
+                    chrono::Local::now().date_naive()
+                ),
+            }
+        );
+    }
+
+    #[test]
+    fn recommend_rest_or_run_backs_off_with_high_load_and_long_streak() {
+        // 28 day average of 1 km a day
+        let schedule = vec![run_entry(
+            days_ago(0),
+            ActivityType::Long,
+            Some(FATIGUE_AVERAGE_DAYS as f64),
+        )];
+        let thresholds = FatigueThresholds::default();
+        assert_eq!(
+            recommend_rest_or_run(&schedule, 7, 1.2, &thresholds),
+            "Rest today"
+        );
+        assert_eq!(
+            recommend_rest_or_run(&schedule, 6, 1.2, &thresholds),
+            "Easy run"
+        );
+        assert_eq!(
+            recommend_rest_or_run(&schedule, 7, 1.1, &thresholds),
+            "Easy run"
+        );
+        assert_eq!(
+            recommend_rest_or_run(&schedule, 6, 1.1, &thresholds),
+            "Train as planned"
+        );
+    }
+
+    #[test]
+    fn recommend_rest_or_run_averages_over_28_days() {
+        let thresholds = FatigueThresholds::default();
+        // counted in the 28 day average of 1 km a day, so 1.2 km is above it by the load ratio
+        let schedule = vec![run_entry(days_ago(27), ActivityType::Long, Some(28.0))];
+        assert_eq!(
+            recommend_rest_or_run(&schedule, 0, 1.2, &thresholds),
+            "Easy run"
+        );
+        // inside the 42 day chronic training load but outside the 28 day average
+        let schedule = vec![run_entry(days_ago(28), ActivityType::Long, Some(28.0))];
+        assert_eq!(
+            recommend_rest_or_run(&schedule, 0, 1.2, &thresholds),
+            "Train as planned"
+        );
+    }
+
+    #[test]
+    fn recommend_rest_or_run_without_chronic_load() {
+        assert_eq!(
+            recommend_rest_or_run(&[], 0, 5.0, &FatigueThresholds::default()),
+            "Train as planned"
+        );
+    }
+
+    fn week_over_week_change(current_km: Option<f64>, previous_km: Option<f64>) -> f64 {
+        let [week_first_date, ..] = current_week_dates(WeekStart::Monday);
+        let schedule: Vec<RunEntry> = [
+            (week_first_date, current_km),
+            (week_first_date - chrono::Duration::weeks(1), previous_km),
+        ]
+        .into_iter()
+        .filter_map(|(date, distance_km)| {
+            Some(run_entry(date, ActivityType::Easy, Some(distance_km?)))
+        })
+        .collect();
+        compute_week_over_week_change(&schedule, WeekStart::Monday)
+    }
+
+    #[test]
+    fn compute_week_over_week_change_in_both_directions() {
+        assert!((week_over_week_change(Some(12.0), Some(10.0)) - 20.0).abs() < 1e-9);
+        assert!((week_over_week_change(Some(5.0), Some(10.0)) + 50.0).abs() < 1e-9);
+        assert_eq!(week_over_week_change(Some(10.0), Some(10.0)), 0.0);
+    }
+
+    #[test]
+    fn compute_week_over_week_change_without_a_previous_week() {
+        assert_eq!(week_over_week_change(Some(10.0), None), 0.0);
+        assert_eq!(week_over_week_change(None, None), 0.0);
+    }
+
+    #[test]
+    fn project_yearly_total_counts_today_once_it_is_logged() {
+        let today = chrono::Local::now().date_naive();
+        let unlogged_projection = 400.0 / today.ordinal0().max(1) as f64 * 365.0;
+        assert_eq!(project_yearly_total(&[], 400.0), unlogged_projection);
+        let rest_today = vec![run_entry(today, ActivityType::Rest, None)];
+        assert_eq!(
+            project_yearly_total(&rest_today, 400.0),
+            unlogged_projection
+        );
+        let run_today = vec![run_entry(today, ActivityType::Easy, Some(10.0))];
+        assert_eq!(
+            project_yearly_total(&run_today, 400.0),
+            400.0 / today.ordinal() as f64 * 365.0
+        );
+    }
+
+    #[test]
+    fn top_n_weekly_distances_orders_truncates_and_spans_year_boundaries() {
+        let date = |year, month, day| NaiveDate::from_ymd_opt(year, month, day).unwrap();
+        let schedule = vec![
+            // both days belong to ISO week 53 of 2020
+            run_entry(date(2020, 12, 31), ActivityType::Easy, Some(20.0)),
+            run_entry(date(2021, 1, 3), ActivityType::Long, Some(25.0)),
+            run_entry(date(2021, 1, 4), ActivityType::Easy, Some(30.0)),
+            run_entry(date(2021, 1, 12), ActivityType::Easy, Some(10.0)),
+            run_entry(date(2021, 1, 13), ActivityType::Rest, None),
+        ];
+        assert_eq!(
+            top_n_weekly_distances(&schedule, 2),
+            vec![(53, 2020, 45.0), (1, 2021, 30.0)]
+        );
+        assert_eq!(top_n_weekly_distances(&schedule, 10).len(), 3);
+        assert!(top_n_weekly_distances(&schedule, 0).is_empty());
+    }
+
+    #[test]
+    fn months_above_goal_counts_months_of_the_current_year() {
+        let date = |year, month, day| NaiveDate::from_ymd_opt(year, month, day).unwrap();
+        let today = date(2024, 3, 15);
+        let schedule = vec![
+            // january adds up to the goal
+            run_entry(date(2024, 1, 1), ActivityType::Easy, Some(60.0)),
+            run_entry(date(2024, 1, 15), ActivityType::Long, Some(50.0)),
+            // exactly the goal counts
+            run_entry(date(2024, 2, 10), ActivityType::Long, Some(100.0)),
+            run_entry(date(2024, 3, 10), ActivityType::Long, Some(99.9)),
+            run_entry(date(2023, 12, 10), ActivityType::Long, Some(500.0)),
+        ];
+        assert_eq!(months_above_goal(&schedule, 100.0, today), 2);
+        assert_eq!(months_above_goal(&schedule, 1000.0, today), 0);
+        assert_eq!(months_above_goal(&[], 100.0, today), 0);
+    }
+
+    #[test]
+    fn months_above_goal_ignores_planned_distances() {
+        let date = |year, month, day| NaiveDate::from_ymd_opt(year, month, day).unwrap();
+        let today = date(2024, 3, 15);
+        let schedule = vec![
+            run_entry(date(2024, 3, 10), ActivityType::Long, Some(60.0)),
+            // later this month, only a target so far
+            run_entry(date(2024, 3, 20), ActivityType::Long, Some(60.0)),
+            run_entry(date(2024, 4, 10), ActivityType::Long, Some(500.0)),
+        ];
+        assert_eq!(months_above_goal(&schedule, 100.0, today), 0);
+        assert_eq!(months_above_goal(&schedule, 60.0, today), 1);
+        // every elapsed month meets a goal of 0, the ones still ahead don't count
+        assert_eq!(months_above_goal(&schedule, 0.0, today), 3);
+    }
+}
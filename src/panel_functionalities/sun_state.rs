@@ -1,3 +1,48 @@
+use chrono::{NaiveTime, Timelike};
+use ratatui::style::{Color, Style};
+use ratatui::text::{Line, Span};
+
+/// Characters of the sun arc in the datetime panel
+pub const SUN_ARC_WIDTH: usize = 20;
+/// Used when the weather api has no sunrise for the day
+pub const DEFAULT_SUNRISE: NaiveTime = NaiveTime::from_hms_opt(6, 0, 0).unwrap();
+/// Used when the weather api has no sunset for the day
+pub const DEFAULT_SUNSET: NaiveTime = NaiveTime::from_hms_opt(20, 0, 0).unwrap();
+const SECONDS_PER_DAY: f64 = 86_400.0;
+
 pub fn get_twilight_pair_today() -> String {
     return String::from("value");
-}
\ No newline at end of file
+}
+
+/// Column of `time` on the arc, midnight at the left edge
+fn sun_arc_position(time: NaiveTime) -> usize {
+    let day_fraction = time.num_seconds_from_midnight() as f64 / SECONDS_PER_DAY;
+    (day_fraction * (SUN_ARC_WIDTH - 1) as f64).round() as usize
+}
+
+/// `SUN_ARC_WIDTH` wide arc with `|` at dawn and dusk and `☀` at `time`, the sun is yellow
+/// between `sunrise` and `sunset` and dark gray otherwise
+pub fn sun_arc_line(time: NaiveTime, sunrise: NaiveTime, sunset: NaiveTime) -> Line<'static> {
+    let sun_position = sun_arc_position(time);
+    let twilight_positions = [sun_arc_position(sunrise), sun_arc_position(sunset)];
+    let sun_color = if (sunrise..sunset).contains(&time) {
+        Color::Yellow
+    } else {
+        Color::DarkGray
+    };
+    let arc_style = Style::default().fg(Color::DarkGray);
+    let arc_char = |column: usize| {
+        if twilight_positions.contains(&column) {
+            '|'
+        } else {
+            '─'
+        }
+    };
+    let before_sun: String = (0..sun_position).map(arc_char).collect();
+    let after_sun: String = (sun_position + 1..SUN_ARC_WIDTH).map(arc_char).collect();
+    Line::from(vec![
+        Span::styled(before_sun, arc_style),
+        Span::styled("☀", Style::default().fg(sun_color)),
+        Span::styled(after_sun, arc_style),
+    ])
+}
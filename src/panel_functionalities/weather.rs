@@ -1,108 +1,454 @@
+use crate::error::AppError;
+use crate::util::validate_date_string;
+use crate::{API_KEY_ENV_VAR, API_KEY_FILE_NAME};
+use chrono::{NaiveDate, NaiveDateTime, NaiveTime};
+use futures_util::future::join_all;
 use open_meteo_api::models::TimeZone;
 use open_meteo_api::query::OpenMeteo;
-use std::collections::HashMap;
-use std::error::Error;
-use std::result;
+use serde::{Deserialize, Serialize};
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::Path;
 
-const MISSING_VALUE_REPLACEMENT: f32 = -512.0;
+const HOURLY_TIME_FORMAT: &str = "%Y-%m-%dT%H:%M";
+const DAILY_TIME_FORMAT: &str = "%Y-%m-%d";
+const GEOCODE_API_URL: &str = "https://geocode.maps.co/search";
+pub const JENA_COORDINATES: (f32, f32) = (50.92722, 11.58611);
 
-// how to use
+/// One entry of `environment_dict["weather_locations"]`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct WeatherLocation {
+    pub name: String,
+    pub lat: f64,
+    pub lon: f64,
+}
 
-pub async fn get_weather_arrays(api_key: &str) -> Result<(), Box<dyn Error>> {
-    // parsed json with (almost) all data you may need
-    // for more info see open-meteo.com/en/docs
-    // sign up to get a free api key here https://geocode.maps.co/
+impl WeatherLocation {
+    /// Reads `name, lat, lon`, the name may contain spaces but no commas
+    pub fn parse(text: &str) -> Option<Self> {
+        let mut parts = text.rsplitn(3, ',').map(str::trim);
+        let lon = parts.next()?.parse::<f64>().ok()?;
+        let lat = parts.next()?.parse::<f64>().ok()?;
+        let name = parts.next().filter(|name| !name.is_empty())?;
+        if !(-90.0..=90.0).contains(&lat) || !(-180.0..=180.0).contains(&lon) {
+            return None;
+        }
+        Some(WeatherLocation {
+            name: name.to_string(),
+            lat,
+            lon,
+        })
+    }
+}
 
-    let toledo_data = OpenMeteo::new()
-        .location("Toledo", api_key)
-        .await? // add location
-        .current_weather()? // add daily weather data
-        .time_zone(TimeZone::EuropeBerlin)?
-        .forecast_days(7)?
-        .daily()?
-        .query()
+impl std::fmt::Display for WeatherLocation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}, {}, {}", self.name, self.lat, self.lon)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct DailyWeatherSummary {
+    pub date: NaiveDate,
+    pub min_temperature: f32,
+    pub max_temperature: f32,
+    pub max_precipitation_probability: f32,
+}
+
+#[derive(Debug, Default, Clone)]
+pub struct HourlyForecast {
+    pub time: Vec<String>,
+    /// `None` where the api has no value for that hour
+    pub temperature_2m: Vec<Option<f32>>,
+    pub precipitation_probability: Vec<Option<f32>>,
+    pub apparent_temperature: Vec<Option<f32>>,
+}
+
+impl HourlyForecast {
+    /// Formatted `[hour, temperature, precipitation probability]` rows starting at the current
+    /// hour, values missing from a shorter vec show as `-`
+    pub fn upcoming_rows(&self, now: NaiveDateTime, hours: usize) -> Vec<[String; 3]> {
+        let mut rows = vec![];
+        for (index, time_string) in self.time.iter().enumerate() {
+            let Ok(hour) = NaiveDateTime::parse_from_str(time_string, HOURLY_TIME_FORMAT) else {
+                continue;
+            };
+            if hour + chrono::Duration::hours(1) <= now {
+                continue;
+            }
+            rows.push([
+                hour.format("%a %H:%M").to_string(),
+                format_weather_value(self.temperature_2m.get(index).copied().flatten(), "°C"),
+                format_weather_value(
+                    self.precipitation_probability.get(index).copied().flatten(),
+                    "%",
+                ),
+            ]);
+            if rows.len() == hours {
+                break;
+            }
+        }
+        rows
+    }
+
+    /// Per day temperature range and highest precipitation probability, days without any
+    /// temperature values are skipped
+    pub fn daily_summaries(&self) -> Vec<DailyWeatherSummary> {
+        let mut daily_summaries: Vec<DailyWeatherSummary> = vec![];
+        for (index, time_string) in self.time.iter().enumerate() {
+            let Ok(hour) = NaiveDateTime::parse_from_str(time_string, HOURLY_TIME_FORMAT) else {
+                continue;
+            };
+            let Some(temperature) = self.temperature_2m.get(index).copied().flatten() else {
+                continue;
+            };
+            let precipitation_probability = self
+                .precipitation_probability
+                .get(index)
+                .copied()
+                .flatten()
+                .unwrap_or(0.0);
+            match daily_summaries.last_mut() {
+                Some(daily_summary) if daily_summary.date == hour.date() => {
+                    daily_summary.min_temperature = daily_summary.min_temperature.min(temperature);
+                    daily_summary.max_temperature = daily_summary.max_temperature.max(temperature);
+                    daily_summary.max_precipitation_probability = daily_summary
+                        .max_precipitation_probability
+                        .max(precipitation_probability);
+                }
+                _ => daily_summaries.push(DailyWeatherSummary {
+                    date: hour.date(),
+                    min_temperature: temperature,
+                    max_temperature: temperature,
+                    max_precipitation_probability: precipitation_probability,
+                }),
+            }
+        }
+        daily_summaries
+    }
+
+    pub fn apparent_temperature_at_noon(&self, date: NaiveDate) -> Option<f32> {
+        let noon_string = date.format("%Y-%m-%dT12:00").to_string();
+        let index = self.time.iter().position(|item| *item == noon_string)?;
+        self.apparent_temperature.get(index).copied().flatten()
+    }
+}
+
+/// One decimal followed by `unit`, `-` for a missing value
+pub fn format_weather_value(value: Option<f32>, unit: &str) -> String {
+    value.map_or("-".to_string(), |value| format!("{value:.1}{unit}"))
+}
+
+/// Reads the geocoding api key from `STARTUP_TUI_API_KEY`, otherwise from `<config_dir>/api_key`
+pub fn load_api_key(config_dir: &Path) -> Result<String, AppError> {
+    if let Ok(api_key) = std::env::var(API_KEY_ENV_VAR) {
+        if !api_key.trim().is_empty() {
+            return Ok(api_key.trim().to_string());
+        }
+    }
+    let api_key = fs::read_to_string(config_dir.join(API_KEY_FILE_NAME))?;
+    if api_key.trim().is_empty() {
+        return Err(AppError::Validation(vec![
+            "api key file is empty".to_string()
+        ]));
+    }
+    Ok(api_key.trim().to_string())
+}
+
+/// Writes `api_key` to `<config_dir>/api_key`, readable and writable by the owner only on unix
+pub fn save_api_key(config_dir: &Path, api_key: &str) -> Result<(), AppError> {
+    fs::create_dir_all(config_dir)?;
+    let mut open_options = OpenOptions::new();
+    open_options.write(true).create(true).truncate(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        open_options.mode(0o600);
+    }
+    let mut file = open_options.open(config_dir.join(API_KEY_FILE_NAME))?;
+    // the mode only applies to new files, a key file saved by an older version is tightened too
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        file.set_permissions(fs::Permissions::from_mode(0o600))?;
+    }
+    file.write_all(api_key.as_bytes())?;
+    Ok(())
+}
+
+/// Looks up `(latitude, longitude)` of the best match for `city_name` on geocode.maps.co
+pub async fn geocode_city(city_name: &str, api_key: &str) -> Result<(f64, f64), AppError> {
+    let response_text = reqwest::Client::new()
+        .get(GEOCODE_API_URL)
+        .query(&[("q", city_name), ("api_key", api_key)])
+        .send()
+        .await?
+        .error_for_status()?
+        .text()
         .await?;
+    let search_results: serde_json::Value = serde_json::from_str(&response_text)?;
+    // coordinates come back as strings, e.g. "lat": "50.9271"
+    let latitude = search_results[0]["lat"]
+        .as_str()
+        .and_then(|item| item.parse::<f64>().ok());
+    let longitude = search_results[0]["lon"]
+        .as_str()
+        .and_then(|item| item.parse::<f64>().ok());
+    match (latitude, longitude) {
+        (Some(latitude), Some(longitude)) => Ok((latitude, longitude)),
+        _ => Err(AppError::WeatherApi(format!(
+            "no geocoding result for {}",
+            city_name
+        ))),
+    }
+}
 
-    let nagoya_data = OpenMeteo::new()
-        .coordinates(35.183334, 136.899994)? // you can also use .coordinates(lat, lon) to set location
-        .current_weather()?
+pub async fn get_hourly_forecast(
+    latitude: f32,
+    longitude: f32,
+) -> Result<HourlyForecast, AppError> {
+    let hourly_data = OpenMeteo::new()
+        .coordinates(latitude, longitude)?
         .time_zone(TimeZone::EuropeBerlin)?
         .forecast_days(7)?
-        .daily()?
+        .hourly()?
         .query()
         .await?;
 
-    // using start date and end date
+    let mut hourly_forecast = HourlyForecast::default();
+    if let Some(hourly_weather_item) = hourly_data.hourly {
+        hourly_forecast.time = hourly_weather_item.time;
+        hourly_forecast.temperature_2m = hourly_weather_item.temperature_2m;
+        hourly_forecast.precipitation_probability = hourly_weather_item.precipitation_probability;
+        hourly_forecast.apparent_temperature = hourly_weather_item.apparent_temperature;
+    }
+    Ok(hourly_forecast)
+}
 
-    let jena_data = OpenMeteo::new()
-        .coordinates(50.927223, 11.586111)? // you can also use .coordinates(lat, lon) to set location
-        .forecast_days(7)?
+/// Current temperature and 7 days of daily values of one `WeatherLocation`, the daily vecs
+/// share the index of `time`
+#[derive(Debug, Default, Clone)]
+pub struct CityWeather {
+    pub current_temperature: Option<f32>,
+    /// `YYYY-MM-DD`
+    pub time: Vec<String>,
+    pub temperature_2m_min: Vec<Option<f32>>,
+    pub temperature_2m_max: Vec<Option<f32>>,
+    pub precipitation_sum: Vec<Option<f32>>,
+    pub sunrise: Vec<String>,
+    pub sunset: Vec<String>,
+}
+
+impl CityWeather {
+    fn index_of(&self, date: NaiveDate) -> Option<usize> {
+        self.time.iter().position(|time_string| {
+            validate_date_string(time_string, DAILY_TIME_FORMAT).ok() == Some(date)
+        })
+    }
+
+    /// Days of the forecast, unreadable entries of `time` are skipped
+    pub fn dates(&self) -> Vec<NaiveDate> {
+        self.time
+            .iter()
+            .filter_map(|time_string| validate_date_string(time_string, DAILY_TIME_FORMAT).ok())
+            .collect()
+    }
+
+    /// Copy holding only the daily values of `date`, `None` for days outside the forecast
+    pub fn for_date(&self, date: NaiveDate) -> Option<CityWeather> {
+        let index = self.index_of(date)?;
+        Some(CityWeather {
+            current_temperature: self.current_temperature,
+            time: vec![self.time[index].clone()],
+            temperature_2m_min: vec![self.temperature_2m_min.get(index).copied().flatten()],
+            temperature_2m_max: vec![self.temperature_2m_max.get(index).copied().flatten()],
+            precipitation_sum: vec![self.precipitation_sum.get(index).copied().flatten()],
+            sunrise: self.sunrise.get(index).cloned().into_iter().collect(),
+            sunset: self.sunset.get(index).cloned().into_iter().collect(),
+        })
+    }
+
+    /// `(min, max)` temperature of `date`, `None` for days outside the forecast
+    pub fn temperature_range_on(&self, date: NaiveDate) -> Option<(Option<f32>, Option<f32>)> {
+        let index = self.index_of(date)?;
+        Some((
+            self.temperature_2m_min.get(index).copied().flatten(),
+            self.temperature_2m_max.get(index).copied().flatten(),
+        ))
+    }
+
+    /// `(sunrise, sunset)` of `date` in Europe/Berlin time, `None` for days outside the forecast
+    /// or unreadable times
+    pub fn sun_times_on(&self, date: NaiveDate) -> Option<(NaiveTime, NaiveTime)> {
+        let index = self.index_of(date)?;
+        let parse_time = |time_string: &String| {
+            NaiveDateTime::parse_from_str(time_string, HOURLY_TIME_FORMAT)
+                .ok()
+                .map(|date_time| date_time.time())
+        };
+        Some((
+            parse_time(self.sunrise.get(index)?)?,
+            parse_time(self.sunset.get(index)?)?,
+        ))
+    }
+}
+
+/// Fills each `None` by linear interpolation between the nearest values on either side, or with
+/// the nearest value for gaps at the start or end. Left unchanged without any value
+pub fn interpolate_missing_weather_values(values: &mut [Option<f32>]) {
+    let known_indices: Vec<usize> = values
+        .iter()
+        .enumerate()
+        .filter_map(|(index, value)| value.map(|_| index))
+        .collect();
+    let (Some(&first_known), Some(&last_known)) = (known_indices.first(), known_indices.last())
+    else {
+        return;
+    };
+    let (first_value, last_value) = (values[first_known], values[last_known]);
+    values[..first_known].fill(first_value);
+    values[last_known + 1..].fill(last_value);
+    for known_pair in known_indices.windows(2) {
+        let (start, end) = (known_pair[0], known_pair[1]);
+        let (Some(start_value), Some(end_value)) = (values[start], values[end]) else {
+            continue;
+        };
+        for (offset, value) in values[start + 1..end].iter_mut().enumerate() {
+            let progress = (offset + 1) as f32 / (end - start) as f32;
+            *value = Some(start_value + (end_value - start_value) * progress);
+        }
+    }
+}
+
+/// `_api_key` is the geocoding key, unused while `loc` already has coordinates since the
+/// open-meteo forecast endpoint needs no key
+pub async fn get_weather_for_location(
+    loc: &WeatherLocation,
+    _api_key: &str,
+) -> Result<CityWeather, AppError> {
+    // for more info see open-meteo.com/en/docs
+    let weather_data = OpenMeteo::new()
+        .coordinates(loc.lat as f32, loc.lon as f32)?
         .current_weather()?
         .time_zone(TimeZone::EuropeBerlin)?
+        .forecast_days(7)?
         .daily()?
         .query()
         .await?;
 
-    // accessing data fields
-    // current_weather, hourly_units, hourly, daily_units, daily have Option type
-    // fields of ".hourly" and ".daily" have Vec<Option<T>> type
-
-    // let temperature = data1.current_weather.unwrap().temperature;
-    // let temperature_2m = data2.hourly.unwrap().temperature_2m;
-    // dbg!(toledo_data);
-    // dbg!(nagoya_data);
-    // dbg!(jena_data);
-    let mut result_hashmap: HashMap<String, Vec<String>> = HashMap::new();
-    let datapack_names: Vec<String> = ["nagoya", "toledo", "jena"]
+    let mut city_weather = CityWeather {
+        current_temperature: weather_data
+            .current_weather
+            .map(|current_weather_item| current_weather_item.temperature),
+        ..Default::default()
+    };
+    if let Some(daily_weather_item) = weather_data.daily {
+        city_weather.time = daily_weather_item.time;
+        city_weather.temperature_2m_min = daily_weather_item.temperature_2m_min;
+        city_weather.temperature_2m_max = daily_weather_item.temperature_2m_max;
+        interpolate_missing_weather_values(&mut city_weather.temperature_2m_min);
+        interpolate_missing_weather_values(&mut city_weather.temperature_2m_max);
+        city_weather.precipitation_sum = daily_weather_item.precipitation_sum;
+        city_weather.sunrise = daily_weather_item.sunrise;
+        city_weather.sunset = daily_weather_item.sunset;
+    }
+    Ok(city_weather)
+}
+
+/// Fetches all `locations` concurrently, results keep the order and name of `locations`
+pub async fn get_weather_for_locations(
+    locations: &[WeatherLocation],
+    api_key: &str,
+) -> Vec<(String, Result<CityWeather, AppError>)> {
+    let city_weather_results = join_all(
+        locations
+            .iter()
+            .map(|location| get_weather_for_location(location, api_key)),
+    )
+    .await;
+    locations
         .iter()
-        .map(|item| item.to_string())
-        .collect();
-    for (index, data_pack) in [nagoya_data, toledo_data, jena_data].iter().enumerate() {
-        if let Some(current_weather_item) = &data_pack.current_weather {
-            result_hashmap.insert(
-                datapack_names[index].clone() + "_current_temperature",
-                vec![current_weather_item.temperature.to_string()],
-            );
-        }
-        if let Some(daily_weather_item) = &data_pack.daily {
-            let precipitation: Vec<f32> = daily_weather_item
-                .precipitation_sum
-                .iter()
-                .map(|item| item.unwrap_or(MISSING_VALUE_REPLACEMENT))
-                .collect();
-            let sunrise_time: Vec<String> = daily_weather_item.sunrise.clone();
-            let sunset_time: Vec<String> = daily_weather_item.sunset.clone();
-            let daily_minimum_temperatures: Vec<f32> = daily_weather_item
-                .temperature_2m_min
-                .iter()
-                .map(|item| item.unwrap_or(MISSING_VALUE_REPLACEMENT))
-                .collect();
-            let daily_maximum_temperatures: Vec<f32> = daily_weather_item
-                .temperature_2m_max
-                .iter()
-                .map(|item| item.unwrap_or(MISSING_VALUE_REPLACEMENT))
-                .collect();
-            result_hashmap.insert(
-                datapack_names[index].clone() + "_precipitation_sum",
-                precipitation.iter().map(|item| {
-                    if *item != MISSING_VALUE_REPLACEMENT {
-                        item.to_string()
-                    }
-                    else {
-                        "-".to_string()
-                    }
-                }).collect(),
-            );
-            result_hashmap.insert(
-                datapack_names[index].clone() + "sunrise_time",
-                sunrise_time
-            );
-            result_hashmap.insert(
-                datapack_names[index].clone() + "sunset_time",
-                sunset_time
-            );
-        }
+        .map(|location| location.name.clone())
+        .zip(city_weather_results)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn interpolate_missing_weather_values_fills_gaps_linearly() {
+        let mut values = vec![Some(10.0), None, None, Some(16.0), None, Some(12.0)];
+        interpolate_missing_weather_values(&mut values);
+        assert_eq!(
+            values,
+            vec![
+                Some(10.0),
+                Some(12.0),
+                Some(14.0),
+                Some(16.0),
+                Some(14.0),
+                Some(12.0)
+            ]
+        );
     }
 
-    Ok(())
+    #[test]
+    fn interpolate_missing_weather_values_extends_the_nearest_value_to_the_ends() {
+        let mut values = vec![None, None, Some(3.5), Some(4.5), None];
+        interpolate_missing_weather_values(&mut values);
+        assert_eq!(
+            values,
+            vec![Some(3.5), Some(3.5), Some(3.5), Some(4.5), Some(4.5)]
+        );
+    }
+
+    #[test]
+    fn interpolate_missing_weather_values_without_any_value() {
+        let mut values: Vec<Option<f32>> = vec![None, None];
+        interpolate_missing_weather_values(&mut values);
+        assert_eq!(values, vec![None, None]);
+        let mut values: Vec<Option<f32>> = vec![];
+        interpolate_missing_weather_values(&mut values);
+        assert!(values.is_empty());
+    }
+
+    #[test]
+    fn request_errors_do_not_contain_the_api_key() {
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .unwrap();
+        // nothing listens on port 1, so the request fails with the url attached
+        let request_error = runtime
+            .block_on(
+                reqwest::Client::new()
+                    .get("http://127.0.0.1:1/search")
+                    .query(&[("q", "Jena"), ("api_key", "secret-api-key")])
+                    .send(),
+            )
+            .unwrap_err();
+        assert!(request_error.to_string().contains("secret-api-key"));
+        let error_text = AppError::from(request_error).to_string();
+        assert!(!error_text.contains("secret-api-key"), "{error_text}");
+    }
+
+    #[test]
+    fn saved_api_key_loads_again_and_is_private() {
+        let config_dir = std::env::temp_dir().join("startup_tui_api_key_round_trip");
+        let saved = save_api_key(&config_dir, "secret-api-key\n");
+        let loaded_api_key = load_api_key(&config_dir);
+        #[cfg(unix)]
+        let file_mode = {
+            use std::os::unix::fs::PermissionsExt;
+            fs::metadata(config_dir.join(API_KEY_FILE_NAME))
+                .map(|metadata| metadata.permissions().mode() & 0o777)
+        };
+        let _ = fs::remove_dir_all(&config_dir);
+        saved.unwrap();
+        assert_eq!(loaded_api_key.unwrap(), "secret-api-key");
+        #[cfg(unix)]
+        assert_eq!(file_mode.unwrap(), 0o600);
+    }
 }
@@ -0,0 +1,171 @@
+use crate::panel_functionalities::weather::DailyWeatherSummary;
+use ratatui::{
+    buffer::Buffer,
+    layout::Rect,
+    style::{Color, Style},
+    widgets::{Block, Paragraph, Widget},
+};
+
+const FORECAST_DAYS: usize = 7;
+const BAR_WIDTH: u16 = 2;
+const BAR_COLOR: Color = Color::Yellow;
+const PRECIPITATION_COLOR: Color = Color::Blue;
+
+/// One column per day with the temperature range as a vertical bar in half-row steps and the
+/// precipitation probability as a blue fill from the bottom, day name on top, max/min below
+#[derive(Debug, Default, Clone)]
+pub struct WeatherForecastPanel<'a> {
+    daily_summaries: Vec<DailyWeatherSummary>,
+    block: Option<Block<'a>>,
+}
+
+impl<'a> WeatherForecastPanel<'a> {
+    pub fn new(daily_summaries: Vec<DailyWeatherSummary>) -> Self {
+        Self {
+            daily_summaries: daily_summaries.into_iter().take(FORECAST_DAYS).collect(),
+            block: None,
+        }
+    }
+
+    pub fn block(mut self, block: Block<'a>) -> Self {
+        self.block = Some(block);
+        self
+    }
+}
+
+/// Character for one chart cell given whether its lower and upper half are covered by the bar
+fn half_block_symbol(lower_covered: bool, upper_covered: bool) -> &'static str {
+    match (lower_covered, upper_covered) {
+        (true, true) => "█",
+        (true, false) => "▄",
+        (false, true) => "▀",
+        (false, false) => " ",
+    }
+}
+
+impl Widget for WeatherForecastPanel<'_> {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        let inner_area = match &self.block {
+            Some(block) => {
+                let inner_area = block.inner(area);
+                block.clone().render(area, buf);
+                inner_area
+            }
+            None => area,
+        };
+        // the panel is hidden or the terminal too small for anything inside the borders
+        if inner_area.is_empty() {
+            return;
+        }
+        // day name row, chart rows, max/min row
+        if self.daily_summaries.is_empty() || inner_area.height < 3 {
+            Paragraph::new("no forecast")
+                .style(Style::default().fg(BAR_COLOR))
+                .render(inner_area, buf);
+            return;
+        }
+        let chart_rows = inner_area.height - 2;
+        let half_row_levels = chart_rows as f32 * 2.0;
+        let lowest_temperature = self
+            .daily_summaries
+            .iter()
+            .map(|daily_summary| daily_summary.min_temperature)
+            .fold(f32::INFINITY, f32::min);
+        let highest_temperature = self
+            .daily_summaries
+            .iter()
+            .map(|daily_summary| daily_summary.max_temperature)
+            .fold(f32::NEG_INFINITY, f32::max);
+        let temperature_span = (highest_temperature - lowest_temperature).max(1.0);
+        let temperature_level = |temperature: f32| {
+            ((temperature - lowest_temperature) / temperature_span * (half_row_levels - 1.0))
+                .round() as u16
+        };
+        let column_width = inner_area.width / FORECAST_DAYS as u16;
+        for (day_index, daily_summary) in self.daily_summaries.iter().enumerate() {
+            let column_x = inner_area.x + day_index as u16 * column_width;
+            let bar_x = column_x + column_width.saturating_sub(BAR_WIDTH) / 2;
+            buf.set_stringn(
+                column_x,
+                inner_area.y,
+                daily_summary.date.format("%a").to_string(),
+                column_width as usize,
+                Style::default().fg(BAR_COLOR),
+            );
+            buf.set_stringn(
+                column_x,
+                inner_area.bottom() - 1,
+                format!(
+                    "{:.0}/{:.0}",
+                    daily_summary.max_temperature, daily_summary.min_temperature
+                ),
+                column_width as usize,
+                Style::default().fg(BAR_COLOR),
+            );
+            let min_level = temperature_level(daily_summary.min_temperature);
+            let max_level = temperature_level(daily_summary.max_temperature);
+            let precipitation_rows = (daily_summary.max_precipitation_probability / 100.0
+                * chart_rows as f32)
+                .round() as u16;
+            // counted from the bottom of the chart
+            for chart_row in 0..chart_rows {
+                let y = inner_area.bottom() - 2 - chart_row;
+                let lower_level = chart_row * 2;
+                let upper_level = lower_level + 1;
+                let symbol = half_block_symbol(
+                    (min_level..=max_level).contains(&lower_level),
+                    (min_level..=max_level).contains(&upper_level),
+                );
+                let background_color = if chart_row < precipitation_rows {
+                    PRECIPITATION_COLOR
+                } else {
+                    Color::Reset
+                };
+                for x in column_x..column_x + column_width {
+                    let Some(cell) = buf.cell_mut((x, y)) else {
+                        continue;
+                    };
+                    cell.set_bg(background_color);
+                    if (bar_x..bar_x + BAR_WIDTH).contains(&x) {
+                        cell.set_symbol(symbol).set_fg(BAR_COLOR);
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ratatui::widgets::Borders;
+
+    fn buffer_lines(buf: &Buffer) -> Vec<String> {
+        (buf.area.top()..buf.area.bottom())
+            .map(|y| {
+                (buf.area.left()..buf.area.right())
+                    .map(|x| buf[(x, y)].symbol())
+                    .collect()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn fallback_text_stays_inside_the_borders() {
+        let area = Rect::new(0, 0, 8, 3);
+        let mut buf = Buffer::empty(area);
+        WeatherForecastPanel::new(vec![])
+            .block(Block::default().borders(Borders::ALL))
+            .render(area, &mut buf);
+        assert_eq!(buffer_lines(&buf), vec!["┌──────┐", "│no for│", "└──────┘"]);
+    }
+
+    #[test]
+    fn empty_area_draws_nothing() {
+        let mut buf = Buffer::empty(Rect::new(0, 0, 4, 2));
+        // a hidden panel gets a zero sized area, possibly past the edge of the buffer
+        WeatherForecastPanel::new(vec![]).render(Rect::new(10, 10, 0, 0), &mut buf);
+        WeatherForecastPanel::new(vec![]).render(Rect::new(0, 1, 4, 0), &mut buf);
+        assert_eq!(buffer_lines(&buf), vec!["    ", "    "]);
+    }
+}
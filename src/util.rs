@@ -0,0 +1,954 @@
+use crate::error::AppError;
+use crate::panel_functionalities::running_schedule::RUN_ENTRY_DATE_FORMAT;
+use chrono::{Datelike, NaiveDate, NaiveDateTime, NaiveTime};
+use crossterm::Command;
+use ratatui::{
+    buffer::Buffer,
+    layout::{Constraint, Direction, Flex, Layout, Rect},
+    style::{Color, Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, Clear, Gauge, Paragraph, StatefulWidget, Widget, Wrap},
+    Frame,
+};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::OnceLock;
+use tui_textarea::TextArea;
+
+const KILOMETRES_PER_MILE: f64 = 1.609344;
+const MILESTONE_MARKER_COLOR: Color = Color::LightBlue;
+const FORM_FOCUSED_COLOR: Color = Color::LightBlue;
+const FORM_UNFOCUSED_COLOR: Color = Color::DarkGray;
+/// Rows taken by one field of [`render_form`], top and bottom border + content
+pub const FORM_FIELD_HEIGHT: u16 = 3;
+pub const HEADER_STYLE: Style = Style::new().fg(Color::Yellow).add_modifier(Modifier::BOLD);
+const SUBTITLE_STYLE: Style = Style::new().fg(Color::Yellow).add_modifier(Modifier::DIM);
+const SPARKLINE_BLOCKS: [char; 8] = ['▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
+/// Weekday labels of the schedule table header, monday first
+pub const WEEKDAY_STRINGS: [&str; 7] = [
+    "月曜日",
+    "火曜日",
+    "水曜日",
+    "木曜日",
+    "金曜日",
+    "土曜日",
+    "日曜日",
+];
+/// Weekday labels of the schedule table header from `ui.weekday_labels`, read once at startup
+/// by [`init_weekday_strings`]
+pub static WEEKDAY_STRINGS_CONFIG: OnceLock<[String; 7]> = OnceLock::new();
+const JAPANESE_WEEKDAY_SUFFIX: &str = "曜日";
+/// Dates further from today than this are shown as dates by [`format_date_relative`]
+const RELATIVE_DATE_MAX_DAYS: i64 = 7;
+/// From here on [`format_km_compact`] switches to thousands
+const COMPACT_KM_THRESHOLD: f64 = 1000.0;
+
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub enum DistanceUnit {
+    #[default]
+    Kilometres,
+    Miles,
+}
+
+impl DistanceUnit {
+    pub fn suffix(&self) -> &'static str {
+        match self {
+            DistanceUnit::Kilometres => "km",
+            DistanceUnit::Miles => "mi",
+        }
+    }
+
+    pub fn from_km(&self, km: f64) -> f64 {
+        match self {
+            DistanceUnit::Kilometres => km,
+            DistanceUnit::Miles => km / KILOMETRES_PER_MILE,
+        }
+    }
+}
+
+/// `HH:MM:SS` for durations of an hour or more, `MM:SS` below that
+pub fn format_duration(seconds: u64) -> String {
+    let hours = seconds / 3600;
+    let minutes = seconds % 3600 / 60;
+    let seconds = seconds % 60;
+    if hours > 0 {
+        format!("{:02}:{:02}:{:02}", hours, minutes, seconds)
+    } else {
+        format!("{:02}:{:02}", minutes, seconds)
+    }
+}
+
+/// `2.1k` from 1000 on, otherwise at most one decimal without a trailing `.0`, e.g. `85`
+/// or `0.5`
+pub fn format_km_compact(km: f64) -> String {
+    let formatted_km = format!("{:.1}", km);
+    // compared after rounding so 999.96 doesn't show as 1000
+    if formatted_km.parse::<f64>().unwrap_or(km) >= COMPACT_KM_THRESHOLD {
+        return format!("{:.1}k", km / 1000.0);
+    }
+    formatted_km
+        .strip_suffix(".0")
+        .map_or(formatted_km.clone(), str::to_string)
+}
+
+/// Inverse of [`format_duration`], accepts both `HH:MM:SS` and `MM:SS`
+pub fn parse_duration(s: &str) -> Result<u64, AppError> {
+    let parts = s
+        .trim()
+        .split(':')
+        .map(|part| part.parse::<u64>())
+        .collect::<Result<Vec<u64>, _>>()
+        .map_err(|_| AppError::Validation(vec![format!("invalid duration: {}", s)]))?;
+    match parts[..] {
+        [minutes, seconds] if seconds < 60 => Ok(minutes * 60 + seconds),
+        [hours, minutes, seconds] if minutes < 60 && seconds < 60 => {
+            Ok(hours * 3600 + minutes * 60 + seconds)
+        }
+        _ => Err(AppError::Validation(vec![format!(
+            "invalid duration: {}",
+            s
+        )])),
+    }
+}
+
+/// `s` read as a date in `format`, a validation error naming `s` otherwise
+pub fn validate_date_string(s: &str, format: &str) -> Result<NaiveDate, AppError> {
+    NaiveDate::parse_from_str(s, format)
+        .map_err(|_| AppError::Validation(vec![format!("Invalid date: {s}")]))
+}
+
+/// Distance in km from a number with an optional `km`, `k`, `mi` or `miles` suffix,
+/// bare numbers are km and negative distances are rejected
+pub fn parse_distance_with_unit(s: &str) -> Result<f64, AppError> {
+    let lowercase_text = s.trim().to_lowercase();
+    let (number_text, unit) = [
+        ("miles", DistanceUnit::Miles),
+        ("mi", DistanceUnit::Miles),
+        ("km", DistanceUnit::Kilometres),
+        ("k", DistanceUnit::Kilometres),
+    ]
+    .into_iter()
+    .find_map(|(suffix, unit)| {
+        lowercase_text
+            .strip_suffix(suffix)
+            .map(|number_text| (number_text, unit))
+    })
+    .unwrap_or((lowercase_text.as_str(), DistanceUnit::Kilometres));
+    match number_text.trim().parse::<f64>() {
+        Ok(distance) if distance.is_finite() && distance >= 0.0 => Ok(match unit {
+            DistanceUnit::Kilometres => distance,
+            DistanceUnit::Miles => distance * KILOMETRES_PER_MILE,
+        }),
+        _ => Err(AppError::Validation(vec![format!(
+            "invalid distance: {}",
+            s
+        )])),
+    }
+}
+
+/// Splits `area` like [`Layout::split`] but never hands a panel less than its entry of
+/// `min_sizes` along `direction`. Panels are collapsed to zero size instead, the last index of
+/// `priority` first, until the remaining ones fit
+pub fn split_layout_with_min_size(
+    area: Rect,
+    direction: Direction,
+    constraints: &[Constraint],
+    min_sizes: &[u16],
+    priority: &[usize],
+) -> Vec<Rect> {
+    let mut collapsed = vec![false; constraints.len()];
+    let mut collapse_order = priority.iter().rev();
+    loop {
+        let effective_constraints =
+            constraints
+                .iter()
+                .zip(&collapsed)
+                .map(|(constraint, is_collapsed)| {
+                    if *is_collapsed {
+                        Constraint::Length(0)
+                    } else {
+                        *constraint
+                    }
+                });
+        let rects = Layout::default()
+            .direction(direction)
+            .constraints(effective_constraints)
+            .split(area)
+            .to_vec();
+        let fits =
+            rects
+                .iter()
+                .zip(min_sizes)
+                .zip(&collapsed)
+                .all(|((rect, min_size), is_collapsed)| {
+                    let size = match direction {
+                        Direction::Horizontal => rect.width,
+                        Direction::Vertical => rect.height,
+                    };
+                    *is_collapsed || size >= *min_size
+                });
+        if fits {
+            return rects;
+        }
+        match collapse_order.next() {
+            Some(index) if *index < collapsed.len() => collapsed[*index] = true,
+            Some(_) => {}
+            None => return rects,
+        }
+    }
+}
+
+/// Rect of the given size centered in `area`, sizes larger than `area` are clamped to it
+pub fn center_the_popup_area(area: Rect, horizontal: Constraint, vertical: Constraint) -> Rect {
+    let [area] = Layout::horizontal([horizontal])
+        .flex(Flex::Center)
+        .areas(area);
+    let [area] = Layout::vertical([vertical]).flex(Flex::Center).areas(area);
+    area
+}
+
+/// `Day: X km | Week: Y km | Month: Z km | Year: W km` from day, week, month and year totals in km
+pub fn format_running_summary(totals: &[f64; 4], unit: DistanceUnit) -> String {
+    ["Day", "Week", "Month", "Year"]
+        .iter()
+        .zip(totals)
+        .map(|(label, total_km)| {
+            format!(
+                "{}: {:.1} {}",
+                label,
+                unit.from_km(*total_km),
+                unit.suffix()
+            )
+        })
+        .collect::<Vec<String>>()
+        .join(" | ")
+}
+
+/// Seconds from `now` until the next occurrence of `target`, today or tomorrow if already past
+pub fn seconds_until_time_of_day(now: NaiveDateTime, target: NaiveTime) -> u64 {
+    let mut target_datetime = now.date().and_time(target);
+    if target_datetime <= now {
+        target_datetime += chrono::Duration::days(1);
+    }
+    (target_datetime - now).num_seconds().max(0) as u64
+}
+
+/// 5 row tall block digit glyphs, 3 columns wide
+const BIG_GLYPH_HEIGHT: usize = 5;
+
+fn big_glyph(character: char) -> [&'static str; BIG_GLYPH_HEIGHT] {
+    match character {
+        '0' => ["███", "█ █", "█ █", "█ █", "███"],
+        '1' => ["  █", "  █", "  █", "  █", "  █"],
+        '2' => ["███", "  █", "███", "█  ", "███"],
+        '3' => ["███", "  █", "███", "  █", "███"],
+        '4' => ["█ █", "█ █", "███", "  █", "  █"],
+        '5' => ["███", "█  ", "███", "  █", "███"],
+        '6' => ["███", "█  ", "███", "█ █", "███"],
+        '7' => ["███", "  █", "  █", "  █", "  █"],
+        '8' => ["███", "█ █", "███", "█ █", "███"],
+        '9' => ["███", "█ █", "███", "  █", "███"],
+        ':' => ["   ", " █ ", "   ", " █ ", "   "],
+        '.' => ["   ", "   ", "   ", "   ", " █ "],
+        _ => ["   "; BIG_GLYPH_HEIGHT],
+    }
+}
+
+/// `text` drawn with block characters, one string per row, for digits, `:` and `.`
+pub fn big_text_lines(text: &str) -> Vec<String> {
+    (0..BIG_GLYPH_HEIGHT)
+        .map(|row| {
+            text.chars()
+                .map(|character| big_glyph(character)[row])
+                .collect::<Vec<&str>>()
+                .join(" ")
+        })
+        .collect()
+}
+
+/// `width` block characters scaled between the smallest and largest value, values are averaged
+/// or repeated to fit `width`, a flat series is drawn at mid height
+pub fn render_sparkline_from_values(values: &[f64], width: usize) -> String {
+    if values.is_empty() || width == 0 {
+        return String::new();
+    }
+    let resampled_values: Vec<f64> = (0..width)
+        .map(|column| {
+            let start = column * values.len() / width;
+            let end = ((column + 1) * values.len() / width).max(start + 1);
+            values[start..end].iter().sum::<f64>() / (end - start) as f64
+        })
+        .collect();
+    let minimum = resampled_values
+        .iter()
+        .copied()
+        .fold(f64::INFINITY, f64::min);
+    let maximum = resampled_values
+        .iter()
+        .copied()
+        .fold(f64::NEG_INFINITY, f64::max);
+    resampled_values
+        .iter()
+        .map(|value| {
+            let level = if maximum > minimum {
+                ((value - minimum) / (maximum - minimum) * (SPARKLINE_BLOCKS.len() - 1) as f64)
+                    .round() as usize
+            } else {
+                SPARKLINE_BLOCKS.len() / 2 - 1
+            };
+            SPARKLINE_BLOCKS[level.min(SPARKLINE_BLOCKS.len() - 1)]
+        })
+        .collect()
+}
+
+/// `HH:MM:SS.d` with deciseconds, for the stopwatch
+pub fn format_stopwatch(elapsed: std::time::Duration) -> String {
+    let deciseconds = elapsed.as_millis() / 100;
+    format!(
+        "{:02}:{:02}:{:02}.{}",
+        deciseconds / 36000,
+        deciseconds % 36000 / 600,
+        deciseconds % 600 / 10,
+        deciseconds % 10
+    )
+}
+
+/// Clears a centered rect of the given size and renders `widget` into it
+pub fn render_centered_popup<W: Widget>(
+    frame: &mut Frame,
+    area: Rect,
+    horizontal: Constraint,
+    vertical: Constraint,
+    widget: W,
+) {
+    let centered_area = center_the_popup_area(area, horizontal, vertical);
+    frame.render_widget(Clear, centered_area);
+    frame.render_widget(widget, centered_area);
+}
+
+/// Bordered block with `title` in the top left and a dimmer `subtitle` in the top right
+/// corner of the same border line
+pub fn render_block_with_title_and_subtitle(title: &str, subtitle: &str) -> Block<'static> {
+    Block::new()
+        .borders(Borders::ALL)
+        .title(Line::from(Span::styled(title.to_string(), HEADER_STYLE)).left_aligned())
+        .title(Line::from(Span::styled(subtitle.to_string(), SUBTITLE_STYLE)).right_aligned())
+}
+
+/// Dimmed `message` centered in `area`, for panels whose data source is empty
+pub fn render_empty_state_placeholder(area: Rect, message: &str, frame: &mut Frame) {
+    let [message_area] = Layout::vertical([Constraint::Length(1)])
+        .flex(Flex::Center)
+        .areas(area);
+    frame.render_widget(
+        Paragraph::new(message.to_string())
+            .style(
+                Style::default()
+                    .fg(Color::DarkGray)
+                    .add_modifier(Modifier::DIM),
+            )
+            .centered()
+            .wrap(Wrap { trim: true }),
+        message_area,
+    );
+}
+
+/// [`render_centered_popup`] for stateful widgets like scrollable tables
+pub fn render_centered_stateful_popup<W: StatefulWidget>(
+    frame: &mut Frame,
+    area: Rect,
+    horizontal: Constraint,
+    vertical: Constraint,
+    widget: W,
+    state: &mut W::State,
+) {
+    let centered_area = center_the_popup_area(area, horizontal, vertical);
+    frame.render_widget(Clear, centered_area);
+    frame.render_stateful_widget(widget, centered_area, state);
+}
+
+/// One labeled input of [`render_form`], `validator` returns the error message for bad input
+pub struct FormField<'a> {
+    pub label: &'a str,
+    pub textarea: TextArea<'a>,
+    pub validator: Option<fn(&str) -> Option<String>>,
+}
+
+impl FormField<'_> {
+    pub fn text(&self) -> String {
+        self.textarea.lines().join("")
+    }
+
+    /// Message of the failed validation, `None` for valid input or fields without a validator
+    pub fn error(&self) -> Option<String> {
+        self.validator.and_then(|validator| validator(&self.text()))
+    }
+}
+
+/// Clears `area` and stacks `fields` in it, the focused one in a highlighted border and
+/// validation errors in the bottom border once something was typed
+pub fn render_form(frame: &mut Frame, area: Rect, fields: &[FormField], focused_index: usize) {
+    frame.render_widget(Clear, area);
+    let field_areas =
+        Layout::vertical(vec![Constraint::Length(FORM_FIELD_HEIGHT); fields.len()]).split(area);
+    for (index, (field, field_area)) in fields.iter().zip(field_areas.iter()).enumerate() {
+        let border_color = if index == focused_index {
+            FORM_FOCUSED_COLOR
+        } else {
+            FORM_UNFOCUSED_COLOR
+        };
+        let mut block = Block::default()
+            .borders(Borders::ALL)
+            .border_style(Style::default().fg(border_color))
+            .title(field.label);
+        if let Some(error) = field.error().filter(|_| !field.text().is_empty()) {
+            block = block.title_bottom(Line::styled(error, Style::default().fg(Color::Red)));
+        }
+        let mut textarea = field.textarea.clone();
+        textarea.set_block(block);
+        frame.render_widget(&textarea, *field_area);
+    }
+}
+
+/// Gauge with `|` ticks drawn over the bar at each milestone, see [`render_gauge_with_milestone_markers`]
+#[derive(Debug, Clone)]
+pub struct MilestoneGauge<'a> {
+    gauge: Gauge<'a>,
+    milestone_ratios: Vec<f64>,
+}
+
+impl Widget for MilestoneGauge<'_> {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        self.gauge.render(area, buf);
+        if area.width == 0 {
+            return;
+        }
+        for milestone_ratio in self.milestone_ratios {
+            if !(0.0..1.0).contains(&milestone_ratio) {
+                continue;
+            }
+            let x = area.x + (milestone_ratio * area.width as f64).round() as u16;
+            for y in area.top()..area.bottom() {
+                let Some(cell) = buf.cell_mut((x, y)) else {
+                    continue;
+                };
+                // leave the label readable
+                if cell.symbol() == " " {
+                    cell.set_symbol("|").set_fg(MILESTONE_MARKER_COLOR);
+                }
+            }
+        }
+    }
+}
+
+/// Overlays milestone ticks on `gauge`, milestones are ratios of the gauge maximum and those
+/// outside `[0, 1)` are skipped. The gauge should have no block, the ticks assume the bar fills the area
+pub fn render_gauge_with_milestone_markers<'a>(
+    gauge: Gauge<'a>,
+    milestones: &[f64],
+) -> impl Widget + 'a {
+    MilestoneGauge {
+        gauge,
+        milestone_ratios: milestones.to_vec(),
+    }
+}
+
+/// Block whose top border fades from one color to another, see [`render_border_with_gradient`]
+#[derive(Debug, Clone)]
+pub struct GradientBorder<'a> {
+    block: Block<'a>,
+    start_color: Color,
+    end_color: Color,
+}
+
+impl Widget for GradientBorder<'_> {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        self.block.render(area, buf);
+        if area.width == 0 || area.height == 0 {
+            return;
+        }
+        let gradient_colors = match (self.start_color, self.end_color) {
+            (Color::Rgb(start_r, start_g, start_b), Color::Rgb(end_r, end_g, end_b))
+                if supports_true_color() =>
+            {
+                Some(((start_r, start_g, start_b), (end_r, end_g, end_b)))
+            }
+            _ => None,
+        };
+        let last_column = area.width.saturating_sub(1).max(1) as f64;
+        for x in area.left()..area.right() {
+            let Some(cell) = buf.cell_mut((x, area.top())) else {
+                continue;
+            };
+            // titles keep their own colors, only the line characters are tinted
+            if !cell
+                .symbol()
+                .chars()
+                .all(|character| ('\u{2500}'..='\u{257F}').contains(&character))
+            {
+                continue;
+            }
+            let color = match gradient_colors {
+                Some((start, end)) => {
+                    let progress = (x - area.left()) as f64 / last_column;
+                    let interpolate = |from: u8, to: u8| {
+                        (from as f64 + (to as f64 - from as f64) * progress).round() as u8
+                    };
+                    Color::Rgb(
+                        interpolate(start.0, end.0),
+                        interpolate(start.1, end.1),
+                        interpolate(start.2, end.2),
+                    )
+                }
+                None => self.start_color,
+            };
+            cell.set_fg(color);
+        }
+    }
+}
+
+/// Renders `block` with its top border colored from `start_color` on the left to `end_color`
+/// on the right. Without rgb colors or a true color terminal the border is `start_color`
+pub fn render_border_with_gradient<'a>(
+    block: Block<'a>,
+    start_color: Color,
+    end_color: Color,
+) -> impl Widget + 'a {
+    GradientBorder {
+        block,
+        start_color,
+        end_color,
+    }
+}
+
+/// Whether the terminal advertises 24 bit colors through `COLORTERM`
+pub fn supports_true_color() -> bool {
+    std::env::var("COLORTERM")
+        .is_ok_and(|colorterm| matches!(colorterm.as_str(), "truecolor" | "24bit"))
+}
+
+/// ISO 8601 week number, 1 to 53
+pub fn week_number_for_date(date: NaiveDate) -> u32 {
+    date.iso_week().week()
+}
+
+/// `RUN_ENTRY_DATE_FORMAT` date string to its day offset from `start`, for `days` days
+pub fn build_date_to_index_map(start: NaiveDate, days: u32) -> HashMap<String, u16> {
+    (0..days)
+        .map(|day_increment| {
+            let date = start + chrono::Duration::days(day_increment as i64);
+            (
+                date.format(RUN_ENTRY_DATE_FORMAT).to_string(),
+                day_increment as u16,
+            )
+        })
+        .collect()
+}
+
+/// `Today`, `Yesterday`, `Tomorrow`, `In N days` or `N days ago` within a week of today,
+/// `RUN_ENTRY_DATE_FORMAT` further out
+pub fn format_date_relative(date: NaiveDate) -> String {
+    match (date - chrono::Local::now().date_naive()).num_days() {
+        0 => "Today".to_string(),
+        -1 => "Yesterday".to_string(),
+        1 => "Tomorrow".to_string(),
+        days if days.abs() > RELATIVE_DATE_MAX_DAYS => {
+            date.format(RUN_ENTRY_DATE_FORMAT).to_string()
+        }
+        days if days > 0 => format!("In {} days", days),
+        days => format!("{} days ago", -days),
+    }
+}
+
+/// Caches `labels`, or the [`WEEKDAY_STRINGS`] without them. Only the first call has an effect,
+/// a reloaded config keeps the labels from startup
+pub fn init_weekday_strings(labels: Option<&[String; 7]>) {
+    let _ = WEEKDAY_STRINGS_CONFIG.set(
+        labels
+            .cloned()
+            .unwrap_or_else(|| WEEKDAY_STRINGS.map(String::from)),
+    );
+}
+
+/// The cached weekday labels, monday first
+pub fn weekday_strings() -> &'static [String; 7] {
+    WEEKDAY_STRINGS_CONFIG.get_or_init(|| WEEKDAY_STRINGS.map(String::from))
+}
+
+/// Index of `s` in `labels`, ignoring surrounding whitespace
+pub fn parse_weekday_label(s: &str, labels: &[&str]) -> Option<usize> {
+    let s = s.trim();
+    labels.iter().position(|label| *label == s)
+}
+
+/// Weekday of one of the [`WEEKDAY_STRINGS`], with or without the `曜日` suffix
+pub fn parse_japanese_weekday(s: &str) -> Option<chrono::Weekday> {
+    let s = s.trim();
+    let weekday_index = parse_weekday_label(s, &WEEKDAY_STRINGS).or_else(|| {
+        WEEKDAY_STRINGS
+            .iter()
+            .position(|label| label.strip_suffix(JAPANESE_WEEKDAY_SUFFIX) == Some(s))
+    })?;
+    chrono::Weekday::try_from(weekday_index as u8).ok()
+}
+
+/// First day of the week for week based totals, `settings.week_start` in the config
+#[derive(Debug, Default, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum WeekStart {
+    #[default]
+    Monday,
+    Sunday,
+}
+
+/// The 7 dates of the current week, starting on `week_start`
+pub fn current_week_dates(week_start: WeekStart) -> [NaiveDate; 7] {
+    let today = chrono::Local::now().date_naive();
+    let days_since_week_start = match week_start {
+        WeekStart::Monday => today.weekday().num_days_from_monday(),
+        WeekStart::Sunday => today.weekday().num_days_from_sunday(),
+    };
+    let first_date = today - chrono::Duration::days(days_since_week_start as i64);
+    std::array::from_fn(|day_increment| first_date + chrono::Duration::days(day_increment as i64))
+}
+
+/// Saves the terminal title on the terminal's title stack (xterm `CSI 22;0 t`)
+pub struct PushTerminalTitle;
+
+impl Command for PushTerminalTitle {
+    fn write_ansi(&self, f: &mut impl fmt::Write) -> fmt::Result {
+        f.write_str("\x1B[22;0t")
+    }
+
+    #[cfg(windows)]
+    fn execute_winapi(&self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+/// Restores the title saved by `PushTerminalTitle`
+pub struct PopTerminalTitle;
+
+impl Command for PopTerminalTitle {
+    fn write_ansi(&self, f: &mut impl fmt::Write) -> fmt::Result {
+        f.write_str("\x1B[23;0t")
+    }
+
+    #[cfg(windows)]
+    fn execute_winapi(&self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn format_duration_switches_to_hours_at_one_hour() {
+        assert_eq!(format_duration(0), "00:00");
+        assert_eq!(format_duration(3599), "59:59");
+        assert_eq!(format_duration(3600), "01:00:00");
+        assert_eq!(format_duration(99 * 3600 + 59 * 60 + 59), "99:59:59");
+    }
+
+    #[test]
+    fn format_km_compact_below_the_threshold() {
+        assert_eq!(format_km_compact(0.0), "0");
+        assert_eq!(format_km_compact(0.5), "0.5");
+        assert_eq!(format_km_compact(85.0), "85");
+        assert_eq!(format_km_compact(85.04), "85");
+        assert_eq!(format_km_compact(999.94), "999.9");
+    }
+
+    #[test]
+    fn format_km_compact_switches_to_thousands() {
+        assert_eq!(format_km_compact(1000.0), "1.0k");
+        assert_eq!(format_km_compact(1500.0), "1.5k");
+        assert_eq!(format_km_compact(12345.0), "12.3k");
+        // rounds to 1000.0 with one decimal
+        assert_eq!(format_km_compact(999.96), "1.0k");
+    }
+
+    #[test]
+    fn parse_duration_round_trips_format_duration() {
+        for seconds in [0, 59, 3599, 3600, 99 * 3600 + 59 * 60 + 59] {
+            assert_eq!(
+                parse_duration(&format_duration(seconds)).ok(),
+                Some(seconds)
+            );
+        }
+        assert_eq!(parse_duration(" 05:30 ").ok(), Some(330));
+    }
+
+    #[test]
+    fn parse_duration_rejects_invalid_durations() {
+        for text in ["", "abc", "12", "05:60", "01:60:00", "1:2:3:4", "-1:00"] {
+            assert!(parse_duration(text).is_err(), "{text:?} should not parse");
+        }
+    }
+
+    #[test]
+    fn format_running_summary_rounds_to_one_decimal_in_km() {
+        assert_eq!(
+            format_running_summary(&[0.0, 999.9, 10000.0, 12.345], DistanceUnit::Kilometres),
+            "Day: 0.0 km | Week: 999.9 km | Month: 10000.0 km | Year: 12.3 km"
+        );
+    }
+
+    #[test]
+    fn format_running_summary_converts_to_miles() {
+        assert_eq!(
+            format_running_summary(&[0.0, 999.9, 10000.0, 1.609344], DistanceUnit::Miles),
+            "Day: 0.0 mi | Week: 621.3 mi | Month: 6213.7 mi | Year: 1.0 mi"
+        );
+    }
+
+    #[test]
+    fn seconds_until_time_of_day_later_today() {
+        let now = NaiveDate::from_ymd_opt(2024, 5, 10)
+            .unwrap()
+            .and_hms_opt(6, 30, 0)
+            .unwrap();
+        let target = NaiveTime::from_hms_opt(7, 45, 30).unwrap();
+        assert_eq!(seconds_until_time_of_day(now, target), 75 * 60 + 30);
+    }
+
+    #[test]
+    fn seconds_until_time_of_day_wraps_to_tomorrow() {
+        let now = NaiveDate::from_ymd_opt(2024, 12, 31)
+            .unwrap()
+            .and_hms_opt(23, 0, 0)
+            .unwrap();
+        let target = NaiveTime::from_hms_opt(6, 0, 0).unwrap();
+        assert_eq!(seconds_until_time_of_day(now, target), 7 * 3600);
+        // a target equal to now is the same time tomorrow
+        assert_eq!(seconds_until_time_of_day(now, now.time()), 24 * 3600);
+    }
+
+    #[test]
+    fn week_number_for_date_follows_iso_year_boundaries() {
+        let date = |year, month, day| NaiveDate::from_ymd_opt(year, month, day).unwrap();
+        assert_eq!(week_number_for_date(date(2020, 12, 31)), 53);
+        assert_eq!(week_number_for_date(date(2021, 1, 3)), 53);
+        assert_eq!(week_number_for_date(date(2021, 1, 4)), 1);
+        // 2019-12-30 already belongs to the first week of 2020
+        assert_eq!(week_number_for_date(date(2019, 12, 30)), 1);
+    }
+
+    #[test]
+    fn build_date_to_index_map_crosses_the_year_boundary() {
+        let date_to_index_map =
+            build_date_to_index_map(NaiveDate::from_ymd_opt(2023, 12, 28).unwrap(), 7);
+        assert_eq!(date_to_index_map.len(), 7);
+        assert_eq!(date_to_index_map.get("12/28/2023"), Some(&0));
+        assert_eq!(date_to_index_map.get("12/31/2023"), Some(&3));
+        assert_eq!(date_to_index_map.get("01/01/2024"), Some(&4));
+        assert_eq!(date_to_index_map.get("01/03/2024"), Some(&6));
+        assert_eq!(date_to_index_map.get("01/04/2024"), None);
+    }
+
+    #[test]
+    fn build_date_to_index_map_crosses_a_leap_day() {
+        let date_to_index_map =
+            build_date_to_index_map(NaiveDate::from_ymd_opt(2024, 2, 28).unwrap(), 3);
+        assert_eq!(date_to_index_map.get("02/29/2024"), Some(&1));
+        assert_eq!(date_to_index_map.get("03/01/2024"), Some(&2));
+        assert!(
+            build_date_to_index_map(NaiveDate::from_ymd_opt(2024, 2, 28).unwrap(), 0).is_empty()
+        );
+    }
+
+    #[test]
+    fn render_sparkline_from_values_handles_edge_cases() {
+        assert_eq!(render_sparkline_from_values(&[], 5), "");
+        assert_eq!(render_sparkline_from_values(&[1.0, 2.0], 0), "");
+        assert_eq!(render_sparkline_from_values(&[4.2], 1), "▄");
+        assert_eq!(render_sparkline_from_values(&[3.0, 3.0, 3.0], 3), "▄▄▄");
+    }
+
+    #[test]
+    fn render_sparkline_from_values_spans_negative_values() {
+        assert_eq!(render_sparkline_from_values(&[-1.0, 0.0, 1.0], 3), "▁▅█");
+    }
+
+    #[test]
+    fn render_sparkline_from_values_resamples_to_the_width() {
+        assert_eq!(render_sparkline_from_values(&[1.0, 2.0, 3.0, 4.0], 2), "▁█");
+        assert_eq!(render_sparkline_from_values(&[0.0, 1.0], 4), "▁▁██");
+    }
+
+    #[test]
+    fn format_date_relative_labels_dates_within_a_week() {
+        let today = chrono::Local::now().date_naive();
+        let relative_label = |days: i64| format_date_relative(today + chrono::Duration::days(days));
+        assert_eq!(relative_label(0), "Today");
+        assert_eq!(relative_label(-1), "Yesterday");
+        assert_eq!(relative_label(1), "Tomorrow");
+        assert_eq!(relative_label(7), "In 7 days");
+        assert_eq!(relative_label(-7), "7 days ago");
+    }
+
+    #[test]
+    fn format_date_relative_falls_back_to_the_date_format() {
+        let today = chrono::Local::now().date_naive();
+        for days in [8, -8] {
+            let date = today + chrono::Duration::days(days);
+            assert_eq!(
+                format_date_relative(date),
+                date.format(RUN_ENTRY_DATE_FORMAT).to_string()
+            );
+        }
+    }
+
+    #[test]
+    fn parse_distance_with_unit_reads_km_and_miles() {
+        assert_eq!(parse_distance_with_unit("5").ok(), Some(5.0));
+        assert_eq!(parse_distance_with_unit(" 10.5 km ").ok(), Some(10.5));
+        assert_eq!(parse_distance_with_unit("3K").ok(), Some(3.0));
+        assert_eq!(
+            parse_distance_with_unit("1 mi").ok(),
+            Some(KILOMETRES_PER_MILE)
+        );
+        assert_eq!(
+            parse_distance_with_unit("2 miles").ok(),
+            Some(2.0 * KILOMETRES_PER_MILE)
+        );
+        assert_eq!(parse_distance_with_unit("0").ok(), Some(0.0));
+    }
+
+    #[test]
+    fn parse_distance_with_unit_rejects_invalid_and_negative_distances() {
+        for text in ["", "abc", "5 miles?", "-3", "-1 mi", "inf", "NaN km"] {
+            assert!(
+                parse_distance_with_unit(text).is_err(),
+                "{text:?} should not parse"
+            );
+        }
+    }
+
+    fn gauge_heights(area_height: u16) -> Vec<u16> {
+        split_layout_with_min_size(
+            Rect::new(0, 0, 20, area_height),
+            Direction::Vertical,
+            &[Constraint::Fill(1); 3],
+            &[3; 3],
+            &[0, 1, 2],
+        )
+        .iter()
+        .map(|rect| rect.height)
+        .collect()
+    }
+
+    #[test]
+    fn split_layout_with_min_size_keeps_all_panels_that_fit() {
+        assert_eq!(gauge_heights(9), vec![3, 3, 3]);
+    }
+
+    #[test]
+    fn split_layout_with_min_size_drops_the_lowest_priority_first() {
+        let heights = gauge_heights(7);
+        assert_eq!(heights[2], 0);
+        assert!(heights[0] >= 3 && heights[1] >= 3);
+        assert_eq!(heights.iter().sum::<u16>(), 7);
+        assert_eq!(gauge_heights(5), vec![5, 0, 0]);
+        assert_eq!(gauge_heights(2), vec![0, 0, 0]);
+    }
+
+    #[test]
+    fn parse_weekday_label_finds_trimmed_labels() {
+        let labels = ["Mo", "Di", "Mi", "Do", "Fr", "Sa", "So"];
+        assert_eq!(parse_weekday_label("Mo", &labels), Some(0));
+        assert_eq!(parse_weekday_label(" So\n", &labels), Some(6));
+        assert_eq!(parse_weekday_label("mo", &labels), None);
+        assert_eq!(parse_weekday_label("", &labels), None);
+        assert_eq!(parse_weekday_label("Mo", &[]), None);
+    }
+
+    #[test]
+    fn parse_japanese_weekday_reads_full_and_short_names() {
+        assert_eq!(parse_japanese_weekday("月曜日"), Some(chrono::Weekday::Mon));
+        assert_eq!(
+            parse_japanese_weekday(" 水曜日 "),
+            Some(chrono::Weekday::Wed)
+        );
+        assert_eq!(parse_japanese_weekday("日"), Some(chrono::Weekday::Sun));
+        assert_eq!(parse_japanese_weekday("土"), Some(chrono::Weekday::Sat));
+        for text in ["", "曜日", "monday", "月曜"] {
+            assert_eq!(
+                parse_japanese_weekday(text),
+                None,
+                "{text:?} should not parse"
+            );
+        }
+    }
+
+    #[test]
+    fn center_the_popup_area_centers_inside_the_area() {
+        let area = Rect::new(10, 5, 100, 50);
+        let popup = center_the_popup_area(area, Constraint::Length(20), Constraint::Length(10));
+        assert_eq!(popup, Rect::new(50, 25, 20, 10));
+    }
+
+    #[test]
+    fn center_the_popup_area_centers_percentages_at_the_origin() {
+        let area = Rect {
+            x: 0,
+            y: 0,
+            width: 100,
+            height: 50,
+        };
+        let popup =
+            center_the_popup_area(area, Constraint::Percentage(50), Constraint::Percentage(50));
+        // the odd row left over goes above the popup
+        assert_eq!(
+            popup,
+            Rect {
+                x: 25,
+                y: 13,
+                width: 50,
+                height: 25,
+            }
+        );
+    }
+
+    #[test]
+    fn center_the_popup_area_fills_the_area_at_full_percentage() {
+        let area = Rect {
+            x: 0,
+            y: 0,
+            width: 100,
+            height: 50,
+        };
+        let popup = center_the_popup_area(
+            area,
+            Constraint::Percentage(100),
+            Constraint::Percentage(100),
+        );
+        assert_eq!(popup, area);
+    }
+
+    #[test]
+    fn center_the_popup_area_rounds_percentages() {
+        let area = Rect::new(0, 0, 101, 51);
+        let popup =
+            center_the_popup_area(area, Constraint::Percentage(50), Constraint::Percentage(50));
+        assert_eq!(popup, Rect::new(25, 13, 51, 25));
+    }
+
+    #[test]
+    fn center_the_popup_area_clamps_to_a_smaller_area() {
+        let area = Rect::new(3, 2, 10, 4);
+        let popup = center_the_popup_area(area, Constraint::Length(20), Constraint::Length(10));
+        assert_eq!(popup, area);
+    }
+
+    #[test]
+    fn center_the_popup_area_handles_a_zero_size_area() {
+        let area = Rect::new(0, 0, 0, 0);
+        let popup =
+            center_the_popup_area(area, Constraint::Percentage(60), Constraint::Percentage(20));
+        assert_eq!(popup.area(), 0);
+    }
+}